@@ -4,18 +4,42 @@ use rustyvision::core::focus::FocusManager;
 use rustyvision::widgets::{
     button::TButton,
     checkboxes::TCheckBoxes,
+    combobox::TComboBox,
     dialog::TDialog,
     inputline::TInputLine,
     label::TLabel,
     listbox::TListBox,
     radiobuttons::TRadioButtons,
-    statusline::TStatusLine,
     footerbar::TFooterBar,
+    history::THistory,
+    menubar::{MenuItem, TMenuBar},
+    menu::{TMenu, TMenuItem},
+    statusline::{TStatusDef, TStatusItem, TStatusLine},
+    validator::TRangeValidator,
 };
+use rustyvision::core::command::{CM_CANCEL, CM_HELP, CM_OK, CM_QUIT};
+use rustyvision::help::compiler::compile;
+use crossterm::event::{KeyCode, KeyModifiers};
 
 use rustyvision::core::view::TView;
-use std::rc::Rc;
-use std::cell::RefCell;
+
+const HELP_SOURCE: &str = "\
+.topic Overview
+This demo shows the rustyvision widgets.
+See {the list box:ListBox} or {Keys}.
+
+.topic ListBox=100
+The list box shows a few entries.
+Right-click or press Shift+F10 for its context menu.
+Back to the {Overview}.
+
+.topic Keys
+Tab moves between controls, F1 opens help and Esc or Alt-X quits.
+Back to the {Overview}.
+";
+
+/// History ID of the name field
+const NAME_HISTORY: u16 = 1;
 
 // A simple root container that holds all top-level views
 struct RootContainer {
@@ -29,9 +53,12 @@ impl RootContainer {
         Self { children: Vec::new(), bounds, focus: FocusManager::new() }
     }
 
+    /// Adds a top-level view; the first focusable one gets the focus
     pub fn add_child(&mut self, mut child: Box<dyn TView>) {
-        if self.children.is_empty() && child.is_focusable() {
+        let has_focus = self.children.iter().any(|child| child.is_focusable());
+        if !has_focus && child.is_focusable() {
             child.set_focus(true);
+            self.focus.index = self.children.len();
         }
         self.children.push(child);
     }
@@ -44,9 +71,7 @@ impl TView for RootContainer {
         }
     }
 
-    fn handle_event(&mut self, event: rustyvision::core::event::TEvent) {
-        use crossterm::event::KeyCode;
-
+    fn handle_event(&mut self, event: rustyvision::core::event::TEvent, queue: &rustyvision::core::event::TEventQueue) {
         if let rustyvision::core::event::TEvent::Key(key) = &event {
             match key.code {
                 KeyCode::Tab => {
@@ -62,7 +87,7 @@ impl TView for RootContainer {
         }
 
         if let Some(focused) = self.focus.current_mut(&mut self.children) {
-            focused.handle_event(event);
+            focused.handle_event(event, queue);
         }
     }
 
@@ -74,10 +99,14 @@ impl TView for RootContainer {
         self.bounds = bounds;
     }
 
-    fn set_focus(&mut self, focused: bool) {
+    fn set_focus(&mut self, _focused: bool) {
         // nothing
     }
 
+    fn help_context(&self) -> Option<u16> {
+        self.focus.current(&self.children).and_then(|child| child.help_context())
+    }
+
 }
 
 fn main() {
@@ -85,12 +114,10 @@ fn main() {
     let screen_height = 25;
 
     // Menu bar
-    /*
     let menubar = TMenuBar::new(
+        vec![MenuItem::new("&File"), MenuItem::new("&Edit"), MenuItem::new("&Help")],
         TRect { x: 0, y: 0, width: screen_width, height: 1 },
-        vec![("File", 1), ("Edit", 2), ("Help", 3)],
     );
-    */
 
     // Dialog
     let mut dialog = TDialog::new("Demo Dialog", TRect { x: 5, y: 2, width: 70, height: 20 });
@@ -98,7 +125,7 @@ fn main() {
 
     let mut input = TInputLine::new(TRect { x: 2, y: 2, width: 30, height: 1 });
     input.set_value("Rusty User");
-    dialog.add_child(Box::new(input));
+    dialog.add_child(Box::new(THistory::new(input, NAME_HISTORY)));
 
     dialog.add_child(Box::new(TCheckBoxes::new(
         TRect { x: 2, y: 4, width: 25, height: 3 },
@@ -110,18 +137,52 @@ fn main() {
         vec!["One", "Two", "Three"],
     )));
 
-    dialog.add_child(Box::new(TListBox::new(
+    dialog.add_child(Box::new(TLabel::new("Age:", TRect { x: 2, y: 12, width: 5, height: 1 })));
+    let mut age = TInputLine::new(TRect { x: 8, y: 12, width: 6, height: 1 });
+    age.set_validator(TRangeValidator::new(1, 150));
+    age.set_value("30");
+    age.set_focus(false);
+    dialog.add_child(Box::new(age));
+
+    dialog.add_child(Box::new(TLabel::new("Password:", TRect { x: 16, y: 12, width: 9, height: 1 })));
+    let mut password = TInputLine::new(TRect { x: 26, y: 12, width: 14, height: 1 });
+    password.set_mask(Some('*'));
+    password.set_reveal_key(KeyCode::F(2), KeyModifiers::NONE);
+    password.set_focus(false);
+    dialog.add_child(Box::new(password));
+
+    let mut list = TListBox::new(
         TRect { x: 40, y: 1, width: 25, height: 5 },
-        vec!["Alpha", "Beta", "Gamma", "Delta", "Epsilon"],
-    )));
-
-    let mut button = TButton::new("OK", TRect { x: 25, y: 14, width: 10, height: 1 });
-    button.set_callback(|| println!("Button pressed!"));
+        vec!["Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa"],
+    );
+    list.set_scroll_bar(true);
+    list.set_help_context(100);
+    list.set_context_menu(TMenu::new(vec![
+        TMenuItem::new("~O~pen", 10).with_keycode(KeyCode::F(3)).with_help_context(101),
+        TMenuItem::new("~R~ename", 11).with_help_context(102),
+        TMenuItem::new("~S~ort", 15).with_submenu(TMenu::new(vec![
+            TMenuItem::new("By ~n~ame", 12),
+            TMenuItem::new("By ~s~ize", 13),
+        ])),
+        TMenuItem::new("~D~elete", 14).with_keycode(KeyCode::Delete).with_help_context(103),
+    ]));
+    dialog.add_child(Box::new(list));
+
+    dialog.add_child(Box::new(TLabel::new("Color:", TRect { x: 40, y: 7, width: 6, height: 1 })));
+    let mut color = TComboBox::new(TRect { x: 40, y: 8, width: 20, height: 1 }, vec!["Red", "Green", "Blue", "Yellow", "Magenta", "Cyan"]);
+    color.set_focus(false);
+    dialog.add_child(Box::new(color));
+
+    let button = TButton::new("~O~K", TRect { x: 20, y: 14, width: 10, height: 2 })
+        .with_command(CM_OK)
+        .with_default(true);
     dialog.add_child(Box::new(button));
+    let cancel = TButton::new("~C~ancel", TRect { x: 35, y: 14, width: 10, height: 2 }).with_command(CM_CANCEL);
+    dialog.add_child(Box::new(cancel));
 
     // Footer bar with interactive content
-    let mut footer = TFooterBar::new(TRect { x: 0, y: screen_height - 1, width: screen_width, height: 1 });
-    let quit_btn = TButton::new("Quit", TRect { x: 65, y: 0, width: 10, height: 1 });
+    let mut footer = TFooterBar::new(TRect { x: 0, y: screen_height - 2, width: screen_width, height: 1 });
+    let quit_btn = TButton::new("~Q~uit", TRect { x: 65, y: 0, width: 10, height: 1 }).with_command(CM_QUIT);
     footer.add_child(Box::new(quit_btn));
 
     // Root container with all top-level views
@@ -131,5 +192,31 @@ fn main() {
     root.add_child(Box::new(footer));
 
     let mut app = TApplication::new(Box::new(root), screen_width, screen_height);
+
+    // Status line: list box keys while the list has focus, Alt-X everywhere
+    let mut status = TStatusLine::new("", TRect { x: 0, y: screen_height - 1, width: screen_width, height: 1 });
+    status.add_def(TStatusDef::new(100, 199, vec![
+        TStatusItem::new("~F3~ Open", KeyCode::F(3), 10),
+        TStatusItem::new("~Del~ Delete", KeyCode::Delete, 14),
+        TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), CM_QUIT).with_modifiers(KeyModifiers::ALT),
+    ]));
+    status.add_def(TStatusDef::new(0, u16::MAX, vec![
+        TStatusItem::new("~F1~ Help", KeyCode::F(1), CM_HELP),
+        TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), CM_QUIT).with_modifiers(KeyModifiers::ALT),
+    ]));
+    status.set_hint_provider(|ctx| {
+        let hint = match ctx {
+            100 => "Pick an entry; right-click for more",
+            101 => "Open the selected entry",
+            102 => "Rename the selected entry",
+            103 => "Delete the selected entry",
+            _ => return None,
+        };
+        Some(hint.to_string())
+    });
+    app.history.add(NAME_HISTORY, "Ferris");
+    app.history.add(NAME_HISTORY, "Rusty User");
+    app.set_status_line(status);
+    app.set_help_file(compile(HELP_SOURCE).expect("demo help source is valid"));
     app.run();
 }
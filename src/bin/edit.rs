@@ -0,0 +1,43 @@
+use rustyvision::core::app::TApplication;
+use rustyvision::core::command::{CM_CLOSE, CM_OPEN, CM_QUIT, CM_SAVE, CM_SAVE_AS};
+use rustyvision::core::event::TEvent;
+use rustyvision::core::rect::TRect;
+use rustyvision::widgets::fileeditor::TFileEditor;
+use rustyvision::widgets::statusline::{TStatusDef, TStatusItem, TStatusLine};
+use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::terminal;
+use std::env;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let (width, height) = terminal::size().unwrap_or((80, 25));
+    let bounds = TRect::new(0, 0, width, height.saturating_sub(1));
+
+    let window = match env::args().nth(1) {
+        Some(path) => match TFileEditor::open(bounds, &path) {
+            Ok(window) => window,
+            Err(err) => {
+                eprintln!("edit: cannot open {}: {}", path, err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => TFileEditor::new(bounds),
+    };
+
+    let warning = window.mixed_line_endings.then(|| window.mixed_line_endings_warning());
+    let mut app = TApplication::new(Box::new(window), width, height);
+    if let Some(warning) = warning {
+        app.put_event(TEvent::Error(warning));
+    }
+    let mut status = TStatusLine::new("", TRect::new(0, height.saturating_sub(1), width, 1));
+    status.add_def(TStatusDef::new(0, u16::MAX, vec![
+        TStatusItem::new("~F2~ Save", KeyCode::F(2), CM_SAVE),
+        TStatusItem::new("~F3~ Open", KeyCode::F(3), CM_OPEN),
+        TStatusItem::new("~F12~ Save As", KeyCode::F(12), CM_SAVE_AS),
+        TStatusItem::new("~Alt-F3~ Close", KeyCode::F(3), CM_CLOSE).with_modifiers(KeyModifiers::ALT),
+        TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), CM_QUIT).with_modifiers(KeyModifiers::ALT),
+    ]));
+    app.set_status_line(status);
+    app.run();
+    ExitCode::SUCCESS
+}
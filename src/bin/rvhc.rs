@@ -0,0 +1,35 @@
+use rustyvision::help::compiler::compile;
+use std::env;
+use std::fs;
+use std::process::exit;
+
+// Help compiler: turns help source text into a compiled help file
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: rvhc <source.txt> <output.hlp>");
+        exit(2);
+    }
+
+    let source = match fs::read_to_string(&args[1]) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", args[1], err);
+            exit(1);
+        }
+    };
+
+    let file = match compile(&source) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("{}: {}", args[1], err);
+            exit(1);
+        }
+    };
+
+    if let Err(err) = file.save(&args[2]) {
+        eprintln!("{}: {}", args[2], err);
+        exit(1);
+    }
+    println!("{}: {} topics", args[2], file.topics.len());
+}
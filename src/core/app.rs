@@ -1,88 +1,531 @@
-use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::clipboard::TClipboard;
+use crate::core::command::{TCommandSet, CM_CANCEL, CM_CLOSE, CM_HELP, CM_NO, CM_OK, CM_QUIT, CM_YES};
+use crate::core::event::{Incoming, TEvent, TEventQueue, TEventSender};
+use crate::core::history::THistoryStore;
+use crate::core::rect::{TPoint, TRect};
+use crate::core::view::{TData, TView};
+use crate::help::helpfile::THelpFile;
 use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::button::TButton;
+use crate::widgets::dialog::TDialog;
+use crate::widgets::dropdownmenu::TDropDownMenu;
+use crate::widgets::helpwindow::THelpWindow;
+use crate::widgets::inputline::TInputLine;
+use crate::widgets::label::TLabel;
+use crate::core::menu::TMenu;
+use crate::widgets::popuplist::TPopupList;
+use crate::widgets::statusline::TStatusLine;
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType},
     execute,
 };
+#[cfg(feature = "async")]
+use crossterm::event::EventStream;
+#[cfg(feature = "async")]
+use futures::{future, select, stream::FuturesUnordered, FutureExt, StreamExt};
+#[cfg(feature = "async")]
+use futures_timer::Delay;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::task::Poll;
 use std::io::{stdout, Write};
-use std::time::Duration;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Longest wait for terminal input when no timer expires sooner, so idle
+/// work keeps running
+const MAX_POLL_TIME: Duration = Duration::from_millis(200);
+
+/// Work the application does whenever it runs out of events, given the
+/// queue to post any events it produces
+pub type IdleCallback = Box<dyn FnMut(&TEventQueue)>;
+
+/// How often the input thread checks whether it should stop
+const INPUT_POLL_TIME: Duration = Duration::from_millis(50);
+
+/// Thread reading terminal input into the application's channel
+struct InputThread {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+}
+
+impl InputThread {
+    fn spawn(sender: TEventSender) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match event::poll(INPUT_POLL_TIME) {
+                    Ok(false) => {}
+                    Ok(true) => {
+                        // Stops once the application is gone
+                        let sent = event::read().is_ok_and(|event| sender.send(Incoming::Terminal(event)));
+                        if !sent {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        Self { handle, stop }
+    }
+
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().ok();
+    }
+}
 
 pub struct TApplication {
     pub root: Box<dyn TView>,
     pub running: bool,
     pub width: u16,
     pub height: u16,
-    pub queue: EventQueue,
+    pub queue: TEventQueue,
+    pub commands: TCommandSet,
+    pub clipboard: TClipboard,
+    pub history: THistoryStore,
+    pub status_line: Option<TStatusLine>,
+    pub help_file: Option<Rc<THelpFile>>,
+    buffer: ScreenBuffer,
+    idle: Option<IdleCallback>,
+    incoming: Receiver<Incoming>,  // Terminal input and events posted from other threads
+    sender: TEventSender,
+    input_thread: Option<InputThread>,
 }
 
 impl TApplication {
     pub fn new(root: Box<dyn TView>, width: u16, height: u16) -> Self {
+        let (sender, incoming) = mpsc::channel();
         Self {
             root,
             running: true,
             width,
             height,
-            queue: EventQueue::new(),
+            queue: TEventQueue::new(),
+            commands: TCommandSet::new(),
+            clipboard: TClipboard::shared(),
+            history: THistoryStore::shared(),
+            status_line: None,
+            help_file: None,
+            buffer: ScreenBuffer::new(width, height),
+            idle: None,
+            incoming,
+            sender: TEventSender::new(sender, Arc::new(Mutex::new(None))),
+            input_thread: None,
         }
     }
 
-    pub fn run(&mut self) {
-        let mut stdout = stdout();
-        enable_raw_mode().unwrap();
-        execute!(stdout, EnterAlternateScreen, Clear(ClearType::All)).unwrap();
+    /// Handle for posting events from other threads
+    pub fn sender(&self) -> TEventSender {
+        self.sender.clone()
+    }
 
-        let mut buffer = ScreenBuffer::new(self.width, self.height);
+    pub fn run(&mut self) {
+        self.enter_terminal();
 
         while self.running {
-            // Draw
-            buffer.clear();
-            self.root.draw(&mut buffer, (0, 0));
-            print!("{}", buffer.flush_to_string());
-            stdout.flush().unwrap();
+            if self.dispatch_pending() {
+                continue;
+            }
 
-            // Step 1: Handle internal events first
-            if let Some(event) = self.queue.get_event() {
+            // Wait for terminal input or events from other threads
+            if let Some(event) = self.poll_terminal() {
                 self.handle_event(event);
-                continue;
             }
+        }
+
+        self.leave_terminal();
+    }
+
+    fn enter_terminal(&mut self) {
+        enable_raw_mode().unwrap();
+        execute!(stdout(), EnterAlternateScreen, EnableMouseCapture, Clear(ClearType::All)).unwrap();
+        self.buffer.invalidate();
+    }
+
+    fn leave_terminal(&mut self) {
+        self.stop_input_thread();
+        execute!(stdout(), DisableMouseCapture, LeaveAlternateScreen).unwrap();
+        disable_raw_mode().unwrap();
+    }
+
+    /// Draws, then handles the next queued event, or else posts the timers
+    /// that expired or runs the idle work. Returns false when there is
+    /// nothing left to do but wait for input.
+    fn dispatch_pending(&mut self) -> bool {
+        self.draw(None);
+
+        // Internal events first
+        if let Some(event) = self.queue.get_event() {
+            self.handle_event(event);
+            return true;
+        }
+
+        // Then timers that expired, then idle work once nothing is left
+        let expired = self.queue.expired_timers();
+        if !expired.is_empty() {
+            for (id, _) in expired.into_iter().rev() {
+                self.queue.put_event(TEvent::Timer(id));
+            }
+            return true;
+        }
+        self.run_idle()
+    }
+
+    /// Sets the callback run whenever the event queue is empty, before
+    /// waiting for terminal input, like TurboVision's `idle`
+    pub fn set_idle<F>(&mut self, callback: F)
+    where
+        F: FnMut(&TEventQueue) + 'static,
+    {
+        self.idle = Some(Box::new(callback));
+    }
+
+    /// Runs the idle callback. Returns true if it posted events.
+    fn run_idle(&mut self) -> bool {
+        match &mut self.idle {
+            Some(idle) => {
+                idle(&self.queue);
+                self.queue.has_pending()
+            }
+            None => false,
+        }
+    }
+
+    /// Installs the status line, drawn over the root view and given the
+    /// first chance at key events. It shares the application's command set.
+    pub fn set_status_line(&mut self, mut status_line: TStatusLine) {
+        status_line.set_command_set(self.commands.clone());
+        self.status_line = Some(status_line);
+    }
+
+    /// Sets the help file opened by F1 or `CM_HELP`
+    pub fn set_help_file(&mut self, help_file: THelpFile) {
+        self.help_file = Some(Rc::new(help_file));
+    }
+
+    /// Shows the help window modally at the topic for `ctx`. `CM_HELP` is
+    /// disabled while it is open, so F1 does not open it again over itself.
+    pub fn show_help(&mut self, ctx: Option<u16>) {
+        let Some(file) = self.help_file.clone() else {
+            return;
+        };
+        if !self.commands.is_enabled(CM_HELP) {
+            return;
+        }
+        let width = self.width.saturating_sub(4).min(70);
+        let height = self.height.saturating_sub(4).min(20);
+        let bounds = TRect::new((self.width - width) / 2, (self.height - height) / 2, width, height);
+        let mut window = THelpWindow::new(file, ctx, bounds);
+        self.commands.disable_command(CM_HELP);
+        self.exec_view(&mut window);
+        self.commands.enable_command(CM_HELP);
+    }
+
+    /// Shows `menu` as a context menu with its top-left corner at `at`,
+    /// moved as needed to stay on screen. Returns the chosen command, or
+    /// `None` if the menu was closed with Esc or a click outside it.
+    pub fn popup_menu(&mut self, menu: TMenu, at: TPoint) -> Option<u16> {
+        let mut popup = TDropDownMenu::new(menu, TRect::new(at.x, at.y, 0, 0));
+        popup.fit_to_screen(self.width, self.height);
+        popup.set_focus(true);
+
+        match self.exec_view(&mut popup) {
+            CM_CANCEL => None,
+            command => Some(command),
+        }
+    }
+
+    /// Shows `items` in a drop-down list below the field at screen bounds
+    /// `field`, or above it when there is no room below, with the item at
+    /// `selected` selected. Returns the index picked, or `None` if the list
+    /// was closed with Esc or a click outside it.
+    pub fn popup_list(&mut self, items: Vec<String>, field: TRect, selected: usize) -> Option<usize> {
+        if items.is_empty() {
+            return None;
+        }
+        let longest = items.iter().map(|item| item.chars().count()).max().unwrap_or(0) as u16;
+        let width = field.width.max(longest + 3).min(self.width);
+        let height = (items.len() as u16).min(8) + 2;
+        let below = field.y + field.height;
+        let y = if below + height <= self.height || field.y < height { below } else { field.y - height };
+        let x = field.x.min(self.width.saturating_sub(width));
+        let bounds = TRect::new(x, y, width, height.min(self.height.saturating_sub(y)));
+        let mut list = TPopupList::new(items, bounds).with_selected(selected);
+
+        match self.exec_view(&mut list) {
+            CM_CANCEL => None,
+            _ => list.selected(),
+        }
+    }
+
+    /// Shows `text` in a modal box closed with OK or Esc
+    pub fn message_box(&mut self, title: &str, text: &str) {
+        let width = (text.chars().count() as u16 + 6).max(30).min(self.width.saturating_sub(4));
+        let height = 7;
+        let bounds = TRect::new((self.width - width) / 2, self.height.saturating_sub(height) / 2, width, height);
+        let mut dialog = TDialog::new(title, bounds);
+        let button_x = (width - 2).saturating_sub(10) / 2;
+        let button = TButton::new("~O~K", TRect::new(button_x, 3, 10, 2)).with_command(CM_OK).with_default(true);
+        dialog.add_child(Box::new(button));
+        dialog.add_child(Box::new(TLabel::new(text, TRect::new(1, 1, width - 4, 1))));
+        self.exec_view(&mut dialog);
+    }
+
+    /// Asks for a line of text, starting with `value`. Returns `None` if
+    /// the box was cancelled.
+    pub fn input_box(&mut self, title: &str, label: &str, value: &str) -> Option<String> {
+        let width = 50.min(self.width.saturating_sub(4));
+        let height = 8;
+        let bounds = TRect::new((self.width - width) / 2, self.height.saturating_sub(height) / 2, width, height);
+        let mut dialog = TDialog::new(title, bounds);
+        let mut input = TInputLine::new(TRect::new(1, 2, width - 4, 1));
+        input.set_value(value);
+        input.select_all();
+        dialog.add_child(Box::new(input));
+        dialog.add_child(Box::new(TLabel::new(label, TRect::new(1, 1, width - 4, 1))));
+        let buttons_x = (width - 2).saturating_sub(24) / 2;
+        let ok = TButton::new("~O~K", TRect::new(buttons_x, 4, 10, 2)).with_command(CM_OK).with_default(true);
+        let cancel = TButton::new("Cancel", TRect::new(buttons_x + 14, 4, 10, 2)).with_command(CM_CANCEL);
+        dialog.add_child(Box::new(ok));
+        dialog.add_child(Box::new(cancel));
+
+        if self.exec_view(&mut dialog) != CM_OK {
+            return None;
+        }
+        match dialog.get_data() {
+            Some(TData::Record(values)) => match values.into_iter().next() {
+                Some(TData::Text(text)) => Some(text),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Asks a yes/no question. Returns `CM_YES`, `CM_NO` or `CM_CANCEL`.
+    pub fn confirm(&mut self, title: &str, text: &str) -> u16 {
+        let width = (text.chars().count() as u16 + 6).max(40).min(self.width.saturating_sub(4));
+        let height = 7;
+        let bounds = TRect::new((self.width - width) / 2, self.height.saturating_sub(height) / 2, width, height);
+        let mut dialog = TDialog::new(title, bounds);
+        let buttons_x = (width - 2).saturating_sub(34) / 2;
+        let yes = TButton::new("~Y~es", TRect::new(buttons_x, 3, 10, 2)).with_command(CM_YES).with_default(true);
+        let no = TButton::new("~N~o", TRect::new(buttons_x + 12, 3, 10, 2)).with_command(CM_NO);
+        let cancel = TButton::new("Cancel", TRect::new(buttons_x + 24, 3, 10, 2)).with_command(CM_CANCEL);
+        dialog.add_child(Box::new(yes));
+        dialog.add_child(Box::new(no));
+        dialog.add_child(Box::new(cancel));
+        dialog.add_child(Box::new(TLabel::new(text, TRect::new(1, 1, width - 4, 1))));
+        self.exec_view(&mut dialog)
+    }
+
+    /// Shows the box a view asked for with a request event and returns the
+    /// answer to send back to it, if any. Other events are returned as `Err`.
+    fn serve_request(&mut self, event: TEvent) -> Result<Option<TEvent>, TEvent> {
+        match event {
+            TEvent::Error(message) => {
+                self.message_box("Error", &message);
+                Ok(None)
+            }
+            TEvent::PopupList(items, field, selected) => Ok(self.popup_list(items, field, selected).map(TEvent::ListChoice)),
+            TEvent::InputBox { title, label, value } => {
+                let reply = match self.input_box(&title, &label, &value) {
+                    Some(text) => TEvent::Reply(CM_OK, text),
+                    None => TEvent::Reply(CM_CANCEL, String::new()),
+                };
+                Ok(Some(reply))
+            }
+            TEvent::Confirm { title, text } => Ok(Some(TEvent::Reply(self.confirm(&title, &text), String::new()))),
+            other => Err(other),
+        }
+    }
 
-            // Step 2: Poll terminal events
-            if event::poll(Duration::from_millis(200)).unwrap() {
-                if let Ok(ev) = event::read() {
-                    let tevent = match ev {
-                        Event::Key(key) => {
-                            if key.code == KeyCode::Esc {
-                                self.running = false;
-                                continue;
-                            }
-                            TEvent::Key(key)
+    /// Runs `view` modally on top of the root view: it receives all terminal
+    /// events until it posts a command for which `view.ends_modal` is true,
+    /// which ends the modal loop and is returned once `view.valid` accepts
+    /// it. Other commands it posts are sent back to it. F1 or `CM_HELP` shows help for
+    /// the view's help context instead. Boxes it asks for are shown and
+    /// answered; other events it posts are forwarded to the application queue.
+    pub fn exec_view(&mut self, view: &mut dyn TView) -> u16 {
+        // Under `run_async` input comes from an `EventStream`; the thread
+        // started here to read it blocking must not compete with the
+        // stream once the modal view closes
+        let had_input_thread = self.input_thread.is_some();
+        let command = self.modal_loop(view);
+        if !had_input_thread {
+            self.stop_input_thread();
+        }
+        command
+    }
+
+    fn modal_loop(&mut self, view: &mut dyn TView) -> u16 {
+        let local = self.queue.modal_queue();
+        loop {
+            self.draw(Some(&*view));
+
+            // Timers set through the application queue wait there until
+            // the modal view closes
+            let expired = self.queue.expired_timers();
+            for &(id, queue) in &expired {
+                if queue == local.id() {
+                    view.handle_event(TEvent::Timer(id), &local);
+                } else {
+                    self.queue.put_timer_event(id);
+                }
+            }
+            if expired.is_empty() {
+                // Events the idle work posts wait in the application queue
+                self.run_idle();
+                // The status line shows the modal view's items, which work
+                // the same as they do over the root view
+                match self.poll_terminal() {
+                    Some(TEvent::Key(key)) => match self.key_command(&key) {
+                        Some(command) => local.put_event(TEvent::Command(command)),
+                        None => view.handle_event(TEvent::Key(key), &local),
+                    },
+                    Some(TEvent::Mouse(mouse)) => {
+                        let pt = TPoint { x: mouse.column, y: mouse.row };
+                        match &mut self.status_line {
+                            Some(line) if line.bounds.contains(pt) => line.handle_event(TEvent::Mouse(mouse), &local),
+                            _ => view.handle_event(TEvent::Mouse(mouse), &local),
                         }
-                        Event::Mouse(mouse) => TEvent::Mouse(mouse),
-                        _ => TEvent::None,
-                    };
+                    }
+                    Some(event) => view.handle_event(event, &local),
+                    None => {}
+                }
+            }
 
-                    self.handle_event(tevent);
+            while let Some(event) = local.get_event() {
+                match event {
+                    // Help opens over the modal view, which stays open
+                    TEvent::Command(CM_HELP) => self.show_help(view.help_context()),
+                    TEvent::Command(command) if view.ends_modal(command) => {
+                        if command == CM_CANCEL || view.valid(command, &local) {
+                            return command;
+                        }
+                    }
+                    TEvent::Command(command) => view.handle_event(TEvent::Command(command), &local),
+                    other => match self.serve_request(other) {
+                        Ok(Some(answer)) => view.handle_event(answer, &local),
+                        Ok(None) => {}
+                        Err(other) => self.queue.put_event(other),
+                    },
                 }
             }
         }
+    }
 
-        // Exit cleanly
-        execute!(stdout, LeaveAlternateScreen).unwrap();
-        disable_raw_mode().unwrap();
+    fn draw(&mut self, modal: Option<&dyn TView>) {
+        self.buffer.clear();
+        self.root.draw(&mut self.buffer, (0, 0));
+        if let Some(status_line) = &mut self.status_line {
+            let ctx = match modal {
+                Some(view) => view.help_context(),
+                None => self.root.help_context(),
+            };
+            status_line.set_help_context(ctx);
+            status_line.draw(&mut self.buffer, (0, 0));
+        }
+        if let Some(view) = modal {
+            view.draw(&mut self.buffer, (0, 0));
+        }
+
+        // Only the cells changed since the last draw are sent
+        let changes = self.buffer.flush_changes_to_string();
+        if !changes.is_empty() {
+            let mut stdout = stdout();
+            print!("{}", changes);
+            stdout.flush().unwrap();
+        }
+    }
+
+    /// Waits for terminal input until the next timer expires, at most
+    /// `MAX_POLL_TIME`. Events posted from other threads end the wait and
+    /// are put on the application queue, even while a modal view runs.
+    fn poll_terminal(&mut self) -> Option<TEvent> {
+        let sender = &self.sender;
+        self.input_thread.get_or_insert_with(|| InputThread::spawn(sender.clone()));
+        let incoming = match self.incoming.recv_timeout(self.poll_timeout()) {
+            Ok(incoming) => incoming,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return None,
+        };
+        self.receive(incoming)
+    }
+
+    /// Time until the next timer expires, at most `MAX_POLL_TIME`
+    fn poll_timeout(&self) -> Duration {
+        match self.queue.next_timer_deadline() {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()).min(MAX_POLL_TIME),
+            None => MAX_POLL_TIME,
+        }
+    }
+
+    /// Returns terminal input as an event; posted events go on the queue
+    fn receive(&mut self, incoming: Incoming) -> Option<TEvent> {
+        match incoming {
+            Incoming::Terminal(Event::Key(key)) => Some(TEvent::Key(key)),
+            Incoming::Terminal(Event::Mouse(mouse)) => Some(TEvent::Mouse(mouse)),
+            Incoming::Terminal(_) => None,
+            Incoming::Command(command) => {
+                self.queue.put_event(TEvent::Command(command));
+                None
+            }
+            Incoming::User(payload) => {
+                self.queue.put_event(TEvent::User(payload));
+                None
+            }
+        }
     }
 
     fn handle_event(&mut self, event: TEvent) {
         match event {
             TEvent::Command(cmd) => {
-                if cmd == 0 {
-                    // Command 0 = close menu or cancel
-                } else if cmd == 9999 {
-                    self.running = false;
+                if cmd == CM_QUIT {
+                    // Views with unsaved data may ask first and post CM_QUIT again
+                    if self.root.valid(CM_QUIT, &self.queue) {
+                        self.running = false;
+                    }
+                } else if cmd == CM_CLOSE {
+                    // Like CM_QUIT, the window may ask to save first
+                    if self.root.valid(CM_CLOSE, &self.queue) {
+                        self.root.handle_event(TEvent::Command(CM_CLOSE), &self.queue);
+                    }
+                } else if cmd == CM_HELP {
+                    self.show_help(self.root.help_context());
                 } else {
-                    // Your app handles command logic here
-                    println!("Command triggered: {}", cmd);
+                    // Other commands go to the focused view
+                    self.root.handle_event(TEvent::Command(cmd), &self.queue);
+                }
+            }
+            TEvent::PopupMenu(menu, at) => {
+                if let Some(cmd) = self.popup_menu(menu, at) {
+                    self.put_event(TEvent::Command(cmd));
+                }
+            }
+            request @ (TEvent::Error(_) | TEvent::PopupList(..) | TEvent::InputBox { .. } | TEvent::Confirm { .. }) => {
+                if let Ok(Some(answer)) = self.serve_request(request) {
+                    self.put_event(answer);
+                }
+            }
+            TEvent::Key(key) => match self.key_command(&key) {
+                Some(cmd) => self.put_event(TEvent::Command(cmd)),
+                // Esc quits unless the root view has a use for it
+                None if key.code == KeyCode::Esc && !self.root.takes_key(&key) => self.put_event(TEvent::Command(CM_QUIT)),
+                None => self.root.handle_event(TEvent::Key(key), &self.queue),
+            },
+            TEvent::Mouse(mouse) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                match &mut self.status_line {
+                    Some(line) if line.bounds.contains(pt) => line.handle_event(TEvent::Mouse(mouse), &self.queue),
+                    _ => self.root.handle_event(TEvent::Mouse(mouse), &self.queue),
                 }
             }
             other => {
@@ -91,7 +534,251 @@ impl TApplication {
         }
     }
 
+    /// Command bound to `key` by the status line, or `CM_HELP` for F1
+    fn key_command(&self, key: &KeyEvent) -> Option<u16> {
+        match self.status_line.as_ref().and_then(|line| line.command_for_key(key)) {
+            Some(command) => Some(command),
+            None if key.code == KeyCode::F(1) => Some(CM_HELP),
+            None => None,
+        }
+    }
+
     pub fn put_event(&self, event: TEvent) {
         self.queue.put_event(event);
     }
+
+    fn stop_input_thread(&mut self) {
+        if let Some(input_thread) = self.input_thread.take() {
+            input_thread.stop();
+        }
+    }
+}
+
+/// Running inside an async program, with the `async` feature
+#[cfg(feature = "async")]
+impl TApplication {
+    /// Runs the application like `run`, but waits for terminal input from
+    /// crossterm's `EventStream` without blocking the thread, and drives
+    /// the futures spawned on the queue. It needs no particular executor:
+    /// any that runs futures on the current thread will do, e.g.
+    /// `futures::executor::block_on` or a Tokio `LocalSet`. Modal views
+    /// still run their own blocking loop, during which spawned futures wait
+    /// and input is read by a thread that stops when the view closes.
+    pub async fn run_async(&mut self) {
+        self.enter_terminal();
+        let mut terminal = EventStream::new();
+        let mut tasks = FuturesUnordered::new();
+
+        while self.running {
+            tasks.extend(self.queue.take_spawned());
+            if self.dispatch_pending() {
+                continue;
+            }
+
+            // Wait for terminal input, a posted event, a finished future or
+            // the next timer
+            let mut input_closed = false;
+            let incoming = {
+                let mut input = terminal.next().fuse();
+                let mut posted = self.next_incoming().fuse();
+                let mut delay = Delay::new(self.poll_timeout()).fuse();
+                select! {
+                    input = input => match input {
+                        Some(Ok(event)) => Some(Incoming::Terminal(event)),
+                        Some(Err(_)) => None,
+                        None => {
+                            input_closed = true;
+                            None
+                        }
+                    },
+                    incoming = posted => Some(incoming),
+                    result = tasks.select_next_some() => Some(Incoming::User(result)),
+                    _ = delay => None,
+                }
+            };
+            if input_closed {
+                break;
+            }
+            if let Some(event) = incoming.and_then(|incoming| self.receive(incoming)) {
+                self.handle_event(event);
+            }
+        }
+
+        self.leave_terminal();
+    }
+
+    /// Spawns `future` on the application's queue; see `TEventQueue::spawn`
+    pub fn spawn<F, T>(&self, future: F)
+    where
+        F: Future<Output = T> + 'static,
+        T: std::any::Any + Send + Sync,
+    {
+        self.queue.spawn(future);
+    }
+
+    /// Next event posted from another thread, or terminal input read by
+    /// the input thread a modal view started
+    fn next_incoming(&self) -> impl Future<Output = Incoming> + '_ {
+        future::poll_fn(move |cx| {
+            self.sender.register_waker(cx.waker());
+            match self.incoming.try_recv() {
+                Ok(incoming) => Poll::Ready(incoming),
+                Err(_) => Poll::Pending,
+            }
+        })
+    }
+}
+
+impl Drop for TApplication {
+    fn drop(&mut self) {
+        self.stop_input_thread();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::menu::TMenuItem;
+    use crate::help::helpfile::THelpTopic;
+    use crate::widgets::statusline::{TStatusDef, TStatusItem};
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+    /// Application whose terminal input is `keys`, already waiting in its channel
+    fn app_with_keys(keys: &[KeyCode]) -> TApplication {
+        let root = TDialog::new("Root", TRect::new(0, 0, 80, 24));
+        let mut app = TApplication::new(Box::new(root), 80, 25);
+        // Stands in for the thread reading the terminal
+        app.input_thread = Some(InputThread { handle: thread::spawn(|| {}), stop: Arc::new(AtomicBool::new(false)) });
+        for &code in keys {
+            app.sender.send(Incoming::Terminal(Event::Key(KeyEvent::new(code, KeyModifiers::NONE))));
+        }
+        app
+    }
+
+    fn help_file() -> THelpFile {
+        THelpFile::new(vec![THelpTopic { name: "Index".to_string(), context: None, lines: vec!["Help".to_string()], links: Vec::new() }])
+    }
+
+    fn ok_dialog() -> TDialog {
+        let mut dialog = TDialog::new("Test", TRect::new(10, 5, 30, 7));
+        dialog.add_child(Box::new(TButton::new("~O~K", TRect::new(9, 3, 10, 2)).with_command(CM_OK).with_default(true)));
+        dialog
+    }
+
+    #[test]
+    fn f1_in_modal_view_shows_help_and_keeps_it_open() {
+        // F1 opens help, Esc closes it, and Enter then presses OK in the dialog
+        let mut app = app_with_keys(&[KeyCode::F(1), KeyCode::Esc, KeyCode::Enter]);
+        app.set_help_file(help_file());
+        assert_eq!(app.exec_view(&mut ok_dialog()), CM_OK);
+        assert!(app.commands.is_enabled(CM_HELP));
+    }
+
+    #[test]
+    fn f1_in_help_window_does_not_open_it_again() {
+        let mut app = app_with_keys(&[KeyCode::F(1), KeyCode::F(1), KeyCode::Esc, KeyCode::Enter]);
+        app.set_help_file(help_file());
+        assert_eq!(app.exec_view(&mut ok_dialog()), CM_OK);
+    }
+
+    fn status_line() -> TStatusLine {
+        let mut line = TStatusLine::new("", TRect::new(0, 24, 80, 1));
+        line.add_def(TStatusDef::new(0, u16::MAX, vec![TStatusItem::new("~F10~ Yes", KeyCode::F(10), CM_YES)]));
+        line
+    }
+
+    #[test]
+    fn status_line_keys_work_in_modal_view() {
+        let mut app = app_with_keys(&[KeyCode::F(10)]);
+        app.set_status_line(status_line());
+        assert_eq!(app.exec_view(&mut ok_dialog()), CM_YES);
+    }
+
+    #[test]
+    fn status_line_clicks_work_in_modal_view() {
+        let mut app = app_with_keys(&[]);
+        app.set_status_line(status_line());
+        let click = MouseEvent { kind: MouseEventKind::Up(MouseButton::Left), column: 2, row: 24, modifiers: KeyModifiers::NONE };
+        app.sender.send(Incoming::Terminal(Event::Mouse(click)));
+        assert_eq!(app.exec_view(&mut ok_dialog()), CM_YES);
+    }
+
+    /// Posts command 100 on `a` and `CM_CANCEL` on Esc, and records the
+    /// commands it receives
+    struct CommandView {
+        received: Vec<u16>,
+    }
+
+    impl TView for CommandView {
+        fn draw(&self, _buffer: &mut ScreenBuffer, _offset: (u16, u16)) {}
+
+        fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+            match event {
+                TEvent::Key(key) if key.code == KeyCode::Char('a') => queue.put_event(TEvent::Command(100)),
+                TEvent::Key(key) if key.code == KeyCode::Esc => queue.put_event(TEvent::Command(CM_CANCEL)),
+                TEvent::Command(command) => self.received.push(command),
+                _ => {}
+            }
+        }
+
+        fn get_bounds(&self) -> TRect {
+            TRect::new(0, 0, 1, 1)
+        }
+
+        fn set_bounds(&mut self, _bounds: TRect) {}
+
+        fn set_focus(&mut self, _focused: bool) {}
+    }
+
+    #[test]
+    fn other_commands_go_to_modal_view() {
+        let mut app = app_with_keys(&[KeyCode::Char('a'), KeyCode::Esc]);
+        let mut view = CommandView { received: Vec::new() };
+        assert_eq!(app.exec_view(&mut view), CM_CANCEL);
+        assert_eq!(view.received, vec![100]);
+    }
+
+    #[test]
+    fn popup_menu_returns_chosen_command() {
+        let mut app = app_with_keys(&[KeyCode::Down, KeyCode::Enter]);
+        let menu = TMenu::new(vec![TMenuItem::new("~O~ne", 100), TMenuItem::new("~T~wo", 101)]);
+        assert_eq!(app.popup_menu(menu, TPoint { x: 5, y: 5 }), Some(101));
+    }
+
+    #[test]
+    fn esc_quits_but_cancel_does_not() {
+        let mut app = app_with_keys(&[]);
+        app.handle_event(TEvent::Command(CM_CANCEL));
+        assert!(app.running);
+
+        app.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(matches!(app.queue.get_event(), Some(TEvent::Command(CM_QUIT))));
+        app.handle_event(TEvent::Command(CM_QUIT));
+        assert!(!app.running);
+    }
+
+    #[test]
+    fn events_posted_from_threads_reach_queue() {
+        let mut app = app_with_keys(&[]);
+        let sender = app.sender();
+        thread::spawn(move || {
+            sender.post_command(42);
+            sender.post_user(7u32);
+        })
+        .join()
+        .unwrap();
+
+        assert!(app.poll_terminal().is_none());
+        assert!(app.poll_terminal().is_none());
+        let mut posted = Vec::new();
+        while let Some(event) = app.queue.get_event() {
+            match event {
+                TEvent::Command(command) => posted.push(u32::from(command)),
+                TEvent::User(payload) => posted.push(*payload.downcast_ref::<u32>().unwrap()),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+        posted.sort();
+        assert_eq!(posted, vec![7, 42]);
+    }
 }
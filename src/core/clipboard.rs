@@ -0,0 +1,36 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+thread_local! {
+    static SHARED: TClipboard = TClipboard::new();
+}
+
+/// Text clipboard shared by editing views. Clones refer to the same text.
+#[derive(Clone, Default)]
+pub struct TClipboard {
+    text: Rc<RefCell<String>>,
+}
+
+impl TClipboard {
+    /// Creates a clipboard of its own, separate from the application's
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the application clipboard, used by views unless given another
+    pub fn shared() -> Self {
+        SHARED.with(|clipboard| clipboard.clone())
+    }
+
+    pub fn set_text(&self, text: &str) {
+        *self.text.borrow_mut() = text.to_string();
+    }
+
+    pub fn text(&self) -> String {
+        self.text.borrow().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.borrow().is_empty()
+    }
+}
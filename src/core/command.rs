@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Command 0 closes a menu or cancels a dialog. It is reserved, so menu
+/// items, including ones that only open a submenu, need another command.
+pub const CM_CANCEL: u16 = 0;
+/// Answers to `TApplication::confirm`
+pub const CM_YES: u16 = 9990;
+pub const CM_NO: u16 = 9991;
+/// File commands, sent to the focused view
+pub const CM_OPEN: u16 = 9992;
+pub const CM_SAVE: u16 = 9993;
+pub const CM_SAVE_AS: u16 = 9994;
+/// Closes the focused window, once `TView::valid` allows it
+pub const CM_CLOSE: u16 = 9995;
+/// Passed to `TView::valid` when a view is about to lose focus
+pub const CM_RELEASED_FOCUS: u16 = 9996;
+/// Accepts a dialog
+pub const CM_OK: u16 = 9997;
+/// Opens help at the focused view's help context
+pub const CM_HELP: u16 = 9998;
+/// Ends the application
+pub const CM_QUIT: u16 = 9999;
+
+/// Shared set of disabled commands. Clones refer to the same set, so views
+/// holding a clone see commands enabled or disabled by the application.
+#[derive(Clone, Default)]
+pub struct TCommandSet {
+    disabled: Rc<RefCell<HashSet<u16>>>,
+}
+
+impl TCommandSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable_command(&self, command: u16) {
+        self.disabled.borrow_mut().remove(&command);
+    }
+
+    pub fn disable_command(&self, command: u16) {
+        self.disabled.borrow_mut().insert(command);
+    }
+
+    pub fn is_enabled(&self, command: u16) -> bool {
+        !self.disabled.borrow().contains(&command)
+    }
+}
@@ -1,24 +1,166 @@
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
-use crossterm::event::{KeyEvent, MouseEvent};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use std::time::{Duration, Instant};
+use crate::core::rect::{TPoint, TRect};
+use crate::core::timer::{QueueId, TTimers, TimerId};
+use crate::core::menu::TMenu;
+use crossterm::event::{Event, KeyEvent, MouseEvent};
+#[cfg(feature = "async")]
+use futures::future::{FutureExt, LocalBoxFuture};
+#[cfg(feature = "async")]
+use std::future::Future;
 
 #[derive(Debug, Clone)]
 pub enum TEvent {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Command(u16),
+    /// Asks the application to show a context menu at a screen position
+    PopupMenu(TMenu, TPoint),
+    /// Asks the application to show a drop-down list below a field, given by
+    /// its screen bounds, with the item at the index given selected. The
+    /// index picked is sent back as `ListChoice`.
+    PopupList(Vec<String>, TRect, usize),
+    /// Index picked from a `PopupList`, sent to the focused view
+    ListChoice(usize),
+    /// Asks the application to show an error message
+    Error(String),
+    /// Asks the application for a line of text; answered with `Reply`
+    InputBox { title: String, label: String, value: String },
+    /// Asks the application for Yes, No or Cancel; answered with `Reply`
+    Confirm { title: String, text: String },
+    /// Answer to `InputBox` or `Confirm`, sent to the focused view: the
+    /// command that closed the box (`CM_OK`, `CM_YES`, `CM_NO` or
+    /// `CM_CANCEL`) and the text entered
+    Reply(u16, String),
+    /// Sent by a list box or table when its focused item or its selection
+    /// changes: the list's ID and the focused item
+    ListSelectionChanged(u16, usize),
+    /// Sent by a list box or table when an item is activated with Enter or
+    /// a double-click: the list's ID and the item
+    ListItemActivated(u16, usize),
+    /// Sent by a tree view when another node is selected: the tree's ID and
+    /// the node's path of child indexes from the top level
+    TreeSelectionChanged(u16, Vec<usize>),
+    /// Sent by a tree view when a node is activated with Enter or a
+    /// double-click: the tree's ID and the node's path
+    TreeNodeActivated(u16, Vec<usize>),
+    /// Sent to the focused view, or the modal view, when a timer set with
+    /// `TEventQueue::set_timer` or `set_repeating_timer` expires
+    Timer(TimerId),
+    /// Posted by the program, e.g. from another thread through a
+    /// `TEventSender` or as the result of a future spawned on the queue;
+    /// receivers downcast the payload to what they expect
+    User(Arc<dyn Any + Send + Sync>),
     None,
 }
 
+/// What wakes the application loop: terminal input read on its own
+/// thread, or an event posted through a `TEventSender`
+pub(crate) enum Incoming {
+    Terminal(Event),
+    Command(u16),
+    User(Arc<dyn Any + Send + Sync>),
+}
+
+/// Handle for posting events to the application from other threads, e.g.
+/// the results of a file scan or a download running in the background.
+/// Posting wakes the application loop at once; the events are then handled
+/// like those put on its `TEventQueue`. Clones post to the same application.
+#[derive(Clone)]
+pub struct TEventSender {
+    sender: Sender<Incoming>,
+    wakeup: Arc<Mutex<Option<Waker>>>, // Task of an async application loop waiting for input
+}
+
+impl TEventSender {
+    pub(crate) fn new(sender: Sender<Incoming>, wakeup: Arc<Mutex<Option<Waker>>>) -> Self {
+        Self { sender, wakeup }
+    }
+
+    /// Posts `TEvent::Command(command)`. Returns false if the application
+    /// is gone.
+    pub fn post_command(&self, command: u16) -> bool {
+        self.send(Incoming::Command(command))
+    }
+
+    /// Posts `TEvent::User` with `payload`. Returns false if the
+    /// application is gone.
+    pub fn post_user<T: Any + Send + Sync>(&self, payload: T) -> bool {
+        self.send(Incoming::User(Arc::new(payload)))
+    }
+
+    /// Wakes `waker` with the next event posted
+    #[cfg(feature = "async")]
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        *self.wakeup.lock().unwrap() = Some(waker.clone());
+    }
+
+    pub(crate) fn send(&self, incoming: Incoming) -> bool {
+        let sent = self.sender.send(incoming).is_ok();
+        if let Some(waker) = self.wakeup.lock().unwrap().take() {
+            waker.wake();
+        }
+        sent
+    }
+}
+
+/// Future run by the application loop, giving the value sent back as
+/// `TEvent::User`
+#[cfg(feature = "async")]
+pub(crate) type SpawnedFuture = LocalBoxFuture<'static, Arc<dyn Any + Send + Sync>>;
+
+/// Returns `mouse` with its position made relative to `origin`. Positions
+/// left of or above the origin become `u16::MAX`, outside any view.
+pub fn mouse_to_local(mouse: MouseEvent, origin: (u16, u16)) -> MouseEvent {
+    MouseEvent {
+        column: mouse.column.checked_sub(origin.0).unwrap_or(u16::MAX),
+        row: mouse.row.checked_sub(origin.1).unwrap_or(u16::MAX),
+        ..mouse
+    }
+}
+
+/// Events posted for the application loop, and the timers it runs.
+/// Clones refer to the same queue.
 #[derive(Clone)]
 pub struct TEventQueue {
     inner: Rc<RefCell<Vec<TEvent>>>,
+    timers: Rc<RefCell<TTimers>>,
+    id: QueueId,                    // Tells whose timers expired
+    #[cfg(feature = "async")]
+    spawned: Rc<RefCell<Vec<SpawnedFuture>>>, // Futures not yet picked up by the loop
+}
+
+impl Default for TEventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TEventQueue {
     pub fn new() -> Self {
         Self {
             inner: Rc::new(RefCell::new(Vec::new())),
+            timers: Rc::new(RefCell::new(TTimers::new())),
+            id: 0,
+            #[cfg(feature = "async")]
+            spawned: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Makes an empty queue for a modal loop, sharing this queue's timers
+    /// and spawned futures
+    pub(crate) fn modal_queue(&self) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(Vec::new())),
+            timers: Rc::clone(&self.timers),
+            id: self.timers.borrow_mut().new_queue(),
+            #[cfg(feature = "async")]
+            spawned: Rc::clone(&self.spawned),
         }
     }
 
@@ -33,4 +175,129 @@ impl TEventQueue {
     pub fn has_pending(&self) -> bool {
         !self.inner.borrow().is_empty()
     }
-}
\ No newline at end of file
+
+    /// Sends one `TEvent::Timer` after `delay`
+    pub fn set_timer(&self, delay: Duration) -> TimerId {
+        self.timers.borrow_mut().start(self.id, delay, None)
+    }
+
+    /// Sends a `TEvent::Timer` every `period` until the timer is killed
+    pub fn set_repeating_timer(&self, period: Duration) -> TimerId {
+        self.timers.borrow_mut().start(self.id, period, Some(period))
+    }
+
+    /// Stops a timer. Returns false if it had already expired or stopped.
+    pub fn kill_timer(&self, id: TimerId) -> bool {
+        self.timers.borrow_mut().stop(id)
+    }
+
+    /// Earliest time a timer expires
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        self.timers.borrow().next_deadline()
+    }
+
+    /// Timers expired by now and the queues that set them, earliest first
+    pub(crate) fn expired_timers(&self) -> Vec<(TimerId, QueueId)> {
+        self.timers.borrow_mut().expire(Instant::now())
+    }
+
+    /// ID the timers this queue sets are expired under
+    pub(crate) fn id(&self) -> QueueId {
+        self.id
+    }
+
+    /// Posts `TEvent::Timer(id)` unless one for that timer is still waiting,
+    /// so a repeating timer left waiting does not pile up events
+    pub(crate) fn put_timer_event(&self, id: TimerId) {
+        let waiting = self.inner.borrow().iter().any(|event| matches!(event, TEvent::Timer(pending) if *pending == id));
+        if !waiting {
+            self.put_event(TEvent::Timer(id));
+        }
+    }
+
+    /// Runs `future` on the application's async loop and posts its result
+    /// as `TEvent::User`. Futures only make progress inside
+    /// `TApplication::run_async`, on whatever executor runs it.
+    #[cfg(feature = "async")]
+    pub fn spawn<F, T>(&self, future: F)
+    where
+        F: Future<Output = T> + 'static,
+        T: Any + Send + Sync,
+    {
+        let future = future.map(|result| Arc::new(result) as Arc<dyn Any + Send + Sync>);
+        self.spawned.borrow_mut().push(future.boxed_local());
+    }
+
+    /// Futures spawned since the last call
+    #[cfg(feature = "async")]
+    pub(crate) fn take_spawned(&self) -> Vec<SpawnedFuture> {
+        std::mem::take(&mut *self.spawned.borrow_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn sender_posts_from_other_threads() {
+        let (sender, incoming) = mpsc::channel();
+        let sender = TEventSender::new(sender, Arc::new(Mutex::new(None)));
+        let worker = sender.clone();
+        thread::spawn(move || {
+            assert!(worker.post_command(42));
+            assert!(worker.post_user(String::from("done")));
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(incoming.recv(), Ok(Incoming::Command(42))));
+        match incoming.recv() {
+            Ok(Incoming::User(payload)) => assert_eq!(payload.downcast_ref::<String>().map(String::as_str), Some("done")),
+            _ => panic!("expected a user event"),
+        }
+
+        // Posting fails once the application is gone
+        drop(incoming);
+        assert!(!sender.post_command(42));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn spawned_futures_are_taken_once() {
+        use futures::task::{noop_waker, ArcWake};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::task::{Context, Poll};
+
+        let queue = TEventQueue::new();
+        queue.spawn(async { 7u32 });
+        queue.spawn(async { "two" });
+        let mut spawned = queue.take_spawned();
+        assert_eq!(spawned.len(), 2);
+        assert!(queue.take_spawned().is_empty());
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match spawned[0].poll_unpin(&mut cx) {
+            Poll::Ready(result) => assert_eq!(result.downcast_ref::<u32>(), Some(&7)),
+            Poll::Pending => panic!("future should be ready"),
+        }
+
+        // A registered waker is woken by the next post
+        struct Flag(AtomicBool);
+        impl ArcWake for Flag {
+            fn wake_by_ref(flag: &Arc<Self>) {
+                flag.0.store(true, Ordering::SeqCst);
+            }
+        }
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let (sender, _incoming) = mpsc::channel();
+        let sender = TEventSender::new(sender, Arc::new(Mutex::new(None)));
+        sender.register_waker(&futures::task::waker(Arc::clone(&flag)));
+        assert!(!flag.0.load(Ordering::SeqCst));
+        sender.post_command(1);
+        assert!(flag.0.load(Ordering::SeqCst));
+    }
+}
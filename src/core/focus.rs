@@ -4,6 +4,12 @@ pub struct FocusManager {
     pub index: usize,
 }
 
+impl Default for FocusManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FocusManager {
     pub fn new() -> Self {
         Self { index: 0 }
@@ -71,7 +77,7 @@ impl FocusManager {
         children.get_mut(self.index)
     }
 
-    pub fn current<'a>(&self, children: &'a [Box<dyn TView>]) -> Option<&'a Box<dyn TView>> {
-        children.get(self.index)
+    pub fn current<'a>(&self, children: &'a [Box<dyn TView>]) -> Option<&'a dyn TView> {
+        children.get(self.index).map(|child| child.as_ref())
     }
 }
@@ -0,0 +1,158 @@
+use std::ops::Range;
+
+/// Text stored as chars around a movable gap, so edits near the last edit
+/// position don't shift the rest of the text. Positions count chars.
+#[derive(Debug, Clone, Default)]
+pub struct GapBuffer {
+    buf: Vec<char>,
+    gap_start: usize,
+    gap_end: usize,
+}
+
+impl From<&str> for GapBuffer {
+    fn from(text: &str) -> Self {
+        let buf: Vec<char> = text.chars().collect();
+        let len = buf.len();
+        Self { buf, gap_start: len, gap_end: len }
+    }
+}
+
+impl GapBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len() - (self.gap_end - self.gap_start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn char_at(&self, pos: usize) -> Option<char> {
+        if pos < self.gap_start {
+            Some(self.buf[pos])
+        } else {
+            self.buf.get(pos + self.gap_end - self.gap_start).copied()
+        }
+    }
+
+    /// Iterates over the chars from `pos` on
+    pub fn chars_from(&self, pos: usize) -> impl Iterator<Item = char> + '_ {
+        (pos..self.len()).map(|i| self.char_at(i).unwrap_or_default())
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> String {
+        range.filter_map(|i| self.char_at(i)).collect()
+    }
+
+    pub fn text(&self) -> String {
+        self.slice(0..self.len())
+    }
+
+    /// Moves the gap so it starts at `pos`
+    fn move_gap(&mut self, pos: usize) {
+        if pos < self.gap_start {
+            let count = self.gap_start - pos;
+            self.buf.copy_within(pos..self.gap_start, self.gap_end - count);
+            self.gap_start -= count;
+            self.gap_end -= count;
+        } else if pos > self.gap_start {
+            let count = pos - self.gap_start;
+            self.buf.copy_within(self.gap_end..self.gap_end + count, self.gap_start);
+            self.gap_start += count;
+            self.gap_end += count;
+        }
+    }
+
+    /// Makes the gap at least `size` chars wide
+    fn grow_gap(&mut self, size: usize) {
+        let gap = self.gap_end - self.gap_start;
+        if gap >= size {
+            return;
+        }
+        let extra = (size - gap).max(self.buf.len() / 2).max(64);
+        let tail = self.buf.len() - self.gap_end;
+        self.buf.resize(self.buf.len() + extra, '\0');
+        let new_end = self.buf.len() - tail;
+        self.buf.copy_within(self.gap_end..self.gap_end + tail, new_end);
+        self.gap_end = new_end;
+    }
+
+    pub fn insert(&mut self, pos: usize, text: &str) {
+        let pos = pos.min(self.len());
+        let chars: Vec<char> = text.chars().collect();
+        self.move_gap(pos);
+        self.grow_gap(chars.len());
+        self.buf[self.gap_start..self.gap_start + chars.len()].copy_from_slice(&chars);
+        self.gap_start += chars.len();
+    }
+
+    /// Removes the chars in `range`, returning them
+    pub fn delete(&mut self, range: Range<usize>) -> String {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        let removed = self.slice(start..end);
+        self.move_gap(start);
+        self.gap_end += end - start;
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserts_at_both_gap_edges() {
+        let mut buffer = GapBuffer::from("ace");
+        buffer.insert(1, "b");
+        // Right at the end of the gap, then right at its start
+        buffer.insert(3, "d");
+        buffer.insert(2, "_");
+        assert_eq!(buffer.text(), "ab_cde");
+        buffer.insert(0, ">");
+        buffer.insert(buffer.len(), "<");
+        assert_eq!(buffer.text(), ">ab_cde<");
+        assert_eq!(buffer.len(), 8);
+    }
+
+    #[test]
+    fn deletes_around_the_gap() {
+        let mut buffer = GapBuffer::from("hello world");
+        buffer.insert(5, ",");
+        assert_eq!(buffer.delete(5..6), ",");
+        // Before the gap, then spanning the text after it
+        assert_eq!(buffer.delete(0..1), "h");
+        assert_eq!(buffer.delete(3..8), "o wor");
+        assert_eq!(buffer.text(), "ellld");
+        assert_eq!(buffer.delete(2..99), "lld");
+        assert_eq!(buffer.delete(5..7), "");
+        assert_eq!(buffer.text(), "el");
+    }
+
+    #[test]
+    fn moving_the_gap_keeps_the_text() {
+        let mut buffer = GapBuffer::from("0123456789");
+        for pos in [9, 0, 5, 5, 10, 3] {
+            buffer.move_gap(pos);
+            assert_eq!(buffer.gap_start, pos);
+            assert_eq!(buffer.text(), "0123456789");
+        }
+        assert_eq!(buffer.char_at(3), Some('3'));
+        assert_eq!(buffer.char_at(4), Some('4'));
+        assert_eq!(buffer.char_at(10), None);
+    }
+
+    #[test]
+    fn grows_for_long_inserts() {
+        let mut buffer = GapBuffer::new();
+        let long = "é".repeat(500);
+        buffer.insert(0, "ab");
+        buffer.insert(1, &long);
+        assert_eq!(buffer.len(), 502);
+        assert_eq!(buffer.slice(0..2), "aé");
+        assert_eq!(buffer.chars_from(500).collect::<String>(), "éb");
+    }
+}
@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+/// First line of every saved history file
+const HEADER: &str = "RVHIST 1";
+
+/// Entries kept per history ID unless `set_limit` says otherwise
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+thread_local! {
+    static SHARED: THistoryStore = THistoryStore::new();
+}
+
+#[derive(Default)]
+struct HistoryLists {
+    lists: BTreeMap<u16, Vec<String>>, // Entries per history ID, oldest first
+    limits: HashMap<u16, usize>,
+}
+
+impl HistoryLists {
+    fn limit(&self, id: u16) -> usize {
+        self.limits.get(&id).copied().unwrap_or(DEFAULT_HISTORY_LIMIT)
+    }
+
+    fn trim(&mut self, id: u16) {
+        let limit = self.limit(id);
+        if let Some(list) = self.lists.get_mut(&id) {
+            let excess = list.len().saturating_sub(limit);
+            list.drain(..excess);
+        }
+    }
+}
+
+/// Previously entered input line values, kept in a list per history ID.
+/// Clones refer to the same lists.
+///
+/// The saved form is plain text, one record per line:
+///
+/// ```text
+/// RVHIST 1
+/// LIST <history ID> <limit>
+/// ITEM <entry>
+/// ```
+///
+/// `ITEM` records belong to the preceding `LIST`, oldest first.
+#[derive(Clone, Default)]
+pub struct THistoryStore {
+    inner: Rc<RefCell<HistoryLists>>,
+}
+
+impl THistoryStore {
+    /// Creates a store of its own, separate from the application's
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the application history store, used by history views unless
+    /// given another
+    pub fn shared() -> Self {
+        SHARED.with(|store| store.clone())
+    }
+
+    /// Records `value` as the newest entry for `id`, dropping an older copy
+    /// of it and the oldest entries past the limit. Empty values are ignored.
+    pub fn add(&self, id: u16, value: &str) {
+        if value.trim().is_empty() || value.contains('\n') {
+            return;
+        }
+        let mut inner = self.inner.borrow_mut();
+        let list = inner.lists.entry(id).or_default();
+        list.retain(|entry| entry != value);
+        list.push(value.to_string());
+        inner.trim(id);
+    }
+
+    /// Returns the entries for `id`, newest first
+    pub fn entries(&self, id: u16) -> Vec<String> {
+        self.inner
+            .borrow()
+            .lists
+            .get(&id)
+            .map_or_else(Vec::new, |list| list.iter().rev().cloned().collect())
+    }
+
+    /// Sets how many entries are kept for `id`
+    pub fn set_limit(&self, id: u16, limit: usize) {
+        let mut inner = self.inner.borrow_mut();
+        inner.limits.insert(id, limit);
+        inner.trim(id);
+    }
+
+    pub fn clear(&self, id: u16) {
+        self.inner.borrow_mut().lists.remove(&id);
+    }
+
+    /// Returns the saved text form
+    pub fn to_saved_string(&self) -> String {
+        let inner = self.inner.borrow();
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+        for (id, list) in &inner.lists {
+            out.push_str(&format!("LIST {} {}\n", id, inner.limit(*id)));
+            for entry in list {
+                out.push_str(&format!("ITEM {}\n", entry));
+            }
+        }
+        out
+    }
+
+    /// Replaces the lists with those in the saved text form
+    pub fn restore(&self, text: &str) -> io::Result<()> {
+        let bad_data = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(bad_data("not a history file"));
+        }
+
+        let mut restored = HistoryLists::default();
+        let mut current = None;
+        for line in lines {
+            let (record, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match record {
+                "LIST" => {
+                    let (id, limit) = rest.split_once(' ').ok_or_else(|| bad_data("bad list record"))?;
+                    let id = id.parse().map_err(|_| bad_data("bad history ID"))?;
+                    let limit = limit.parse().map_err(|_| bad_data("bad history limit"))?;
+                    restored.limits.insert(id, limit);
+                    restored.lists.insert(id, Vec::new());
+                    current = Some(id);
+                }
+                "ITEM" => {
+                    let id = current.ok_or_else(|| bad_data("item outside a list"))?;
+                    restored.lists.entry(id).or_default().push(rest.to_string());
+                }
+                "" => {}
+                _ => return Err(bad_data("unknown record")),
+            }
+        }
+        let ids: Vec<u16> = restored.lists.keys().copied().collect();
+        for id in ids {
+            restored.trim(id);
+        }
+        *self.inner.borrow_mut() = restored;
+        Ok(())
+    }
+
+    /// Loads the lists saved by `save`, replacing the current ones
+    pub fn load(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = fs::read_to_string(path)?;
+        self.restore(&text)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_saved_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_again_moves_entry_to_front() {
+        let store = THistoryStore::new();
+        for value in ["one", "two", "three", "one", "  "] {
+            store.add(1, value);
+        }
+        assert_eq!(store.entries(1), vec!["one", "three", "two"]);
+        assert!(store.entries(2).is_empty());
+    }
+
+    #[test]
+    fn limit_applies_per_id() {
+        let store = THistoryStore::new();
+        store.set_limit(1, 2);
+        for value in ["a", "b", "c"] {
+            store.add(1, value);
+            store.add(2, value);
+        }
+        assert_eq!(store.entries(1), vec!["c", "b"]);
+        assert_eq!(store.entries(2), vec!["c", "b", "a"]);
+
+        // Lowering a limit drops the oldest entries at once
+        store.set_limit(2, 1);
+        assert_eq!(store.entries(2), vec!["c"]);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let store = THistoryStore::new();
+        store.set_limit(7, 3);
+        for value in ["first", "second entry", "third", "fourth"] {
+            store.add(7, value);
+        }
+        store.add(2, "other");
+        let text = store.to_saved_string();
+        assert_eq!(text, "RVHIST 1\nLIST 2 20\nITEM other\nLIST 7 3\nITEM second entry\nITEM third\nITEM fourth\n");
+
+        let path = std::env::temp_dir().join(format!("rustyvision-history-{}.txt", std::process::id()));
+        store.save(&path).unwrap();
+        let loaded = THistoryStore::new();
+        loaded.add(9, "replaced");
+        loaded.load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.entries(7), vec!["fourth", "third", "second entry"]);
+        assert_eq!(loaded.entries(2), vec!["other"]);
+        assert!(loaded.entries(9).is_empty());
+        // The limit is restored with the list
+        loaded.add(7, "fifth");
+        assert_eq!(loaded.entries(7).len(), 3);
+    }
+
+    #[test]
+    fn restore_rejects_bad_text() {
+        let store = THistoryStore::new();
+        store.add(1, "kept");
+        assert!(store.restore("RVHIST 2\n").is_err());
+        assert!(store.restore("RVHIST 1\nITEM orphan\n").is_err());
+        assert!(store.restore("RVHIST 1\nLIST x 20\n").is_err());
+        assert_eq!(store.entries(1), vec!["kept"]);
+    }
+}
@@ -0,0 +1,12 @@
+//! Timings and steps shared by the views that take mouse and typed input
+
+use std::time::Duration;
+
+/// Longest pause between two clicks of a double-click
+pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Pause after which typing starts a new search instead of extending it
+pub const SEARCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Lines or columns moved by one step of the mouse wheel
+pub const WHEEL_STEP: usize = 3;
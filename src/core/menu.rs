@@ -0,0 +1,135 @@
+use crossterm::event::KeyCode;
+
+/// A single menu item entry
+#[derive(Debug, Clone)]
+pub struct TMenuItem {
+    pub label: String,                   // Label text with ~hotkey~ markers, e.g. "~O~pen"
+    pub command: u16,                    // Numeric command ID
+    pub keycode: Option<KeyCode>,        // Optional keyboard shortcut
+    pub disabled: bool,                  // Whether the item is disabled
+    pub help_context: Option<u16>,       // Optional help context ID
+    pub submenu: Option<TMenu>,          // Optional nested submenu
+}
+
+impl TMenuItem {
+    pub fn new(label: &str, command: u16) -> Self {
+        Self {
+            label: label.to_string(),
+            command,
+            keycode: None,
+            disabled: false,
+            help_context: None,
+            submenu: None,
+        }
+    }
+
+    pub fn with_keycode(mut self, key: KeyCode) -> Self {
+        self.keycode = Some(key);
+        self
+    }
+
+    pub fn with_help_context(mut self, ctx: u16) -> Self {
+        self.help_context = Some(ctx);
+        self
+    }
+
+    pub fn with_submenu(mut self, submenu: TMenu) -> Self {
+        self.submenu = Some(submenu);
+        self
+    }
+
+    pub fn disabled(mut self, val: bool) -> Self {
+        self.disabled = val;
+        self
+    }
+
+    /// Parses and returns the first hotkey from the label (after a `~`)
+    pub fn hotkey(&self) -> Option<char> {
+        hotkey_of(&self.label)
+    }
+
+    /// Returns the label string with hotkey markers (`~`) stripped
+    pub fn clean_label(&self) -> String {
+        self.label.replace("~", "")
+    }
+
+    /// Returns the display text of the keyboard shortcut, e.g. "F3" or "Del"
+    pub fn shortcut_text(&self) -> Option<String> {
+        self.keycode.map(key_name)
+    }
+}
+
+/// Returns the lower-cased hotkey of a label with `~hotkey~` markers: the
+/// character after the first `~`
+pub fn hotkey_of(label: &str) -> Option<char> {
+    label
+        .find('~')
+        .and_then(|i| label[i + 1..].chars().next())
+        .map(|c| c.to_ascii_lowercase())
+}
+
+/// Returns a short display name for a key code, as shown next to menu items
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Bksp".to_string(),
+        KeyCode::Insert => "Ins".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TMenu {
+    pub items: Vec<TMenuItem>,
+}
+
+impl TMenu {
+    pub fn new(items: Vec<TMenuItem>) -> Self {
+        Self { items }
+    }
+
+    pub fn add_item(&mut self, item: TMenuItem) {
+        self.items.push(item);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Finds the enabled item bound to `key`, searching submenus as well
+    pub fn find_shortcut(&self, key: KeyCode) -> Option<&TMenuItem> {
+        self.items.iter().filter(|item| !item.disabled).find_map(|item| {
+            if item.keycode == Some(key) {
+                Some(item)
+            } else {
+                item.submenu.as_ref().and_then(|submenu| submenu.find_shortcut(key))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkey_after_multibyte_characters() {
+        assert_eq!(hotkey_of("Über ~D~atei"), Some('d'));
+        assert_eq!(hotkey_of("~Ö~ffnen"), Some('Ö'));
+        assert_eq!(hotkey_of("No hotkey"), None);
+        assert_eq!(TMenuItem::new("Über ~D~atei", 1).hotkey(), Some('d'));
+    }
+}
@@ -2,4 +2,11 @@ pub mod event;
 pub mod rect;
 pub mod view;
 pub mod app;
-pub mod focus;
\ No newline at end of file
+pub mod focus;
+pub mod command;
+pub mod clipboard;
+pub mod history;
+pub mod gapbuffer;
+pub mod timer;
+pub mod menu;
+pub mod input;
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+/// Identifies a timer in the `TEvent::Timer` events it sends
+pub type TimerId = u32;
+
+/// Identifies the event queue that set a timer: the application's, or the
+/// local queue of a modal loop
+pub(crate) type QueueId = u32;
+
+struct Timer {
+    id: TimerId,
+    queue: QueueId,
+    deadline: Instant,
+    period: Option<Duration>, // Time between events of a repeating timer
+}
+
+/// Pending timers of an application, set through its `TEventQueue`
+#[derive(Default)]
+pub struct TTimers {
+    next_id: TimerId,
+    next_queue: QueueId,
+    timers: Vec<Timer>,
+}
+
+impl TTimers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a timer for `queue` that first expires after `delay` and
+    /// then every `period`, if given
+    pub(crate) fn start(&mut self, queue: QueueId, delay: Duration, period: Option<Duration>) -> TimerId {
+        self.next_id = self.next_id.wrapping_add(1);
+        let id = self.next_id;
+        self.timers.push(Timer { id, queue, deadline: Instant::now() + delay, period });
+        id
+    }
+
+    /// A new ID for a queue sharing these timers; the first queue is 0
+    pub(crate) fn new_queue(&mut self) -> QueueId {
+        self.next_queue = self.next_queue.wrapping_add(1);
+        self.next_queue
+    }
+
+    /// Stops a timer. Returns false if it had already expired or stopped.
+    pub fn stop(&mut self, id: TimerId) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != count
+    }
+
+    /// Earliest time a timer expires
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Returns the timers expired by `now` with the queues that set them,
+    /// earliest first. One-shot timers are removed; repeating ones are
+    /// moved on by whole periods, so a timer that fell behind expires once
+    /// rather than once per period.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<(TimerId, QueueId)> {
+        let mut expired: Vec<(Instant, TimerId, QueueId)> = Vec::new();
+        self.timers.retain_mut(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+            expired.push((timer.deadline, timer.id, timer.queue));
+            match timer.period {
+                Some(period) if !period.is_zero() => {
+                    while timer.deadline <= now {
+                        timer.deadline += period;
+                    }
+                    true
+                }
+                _ => false,
+            }
+        });
+        expired.sort();
+        expired.into_iter().map(|(_, id, queue)| (id, queue)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn timers_expire_earliest_first() {
+        let mut timers = TTimers::new();
+        let now = Instant::now();
+        let late = timers.start(0, 30 * MS, None);
+        let early = timers.start(1, 10 * MS, None);
+        let pending = timers.start(0, 1000 * MS, None);
+
+        assert_eq!(timers.expire(now + 50 * MS), vec![(early, 1), (late, 0)]);
+        assert!(timers.expire(now + 50 * MS).is_empty());
+        assert!(timers.stop(pending));
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn a_repeating_timer_that_fell_behind_expires_once() {
+        let mut timers = TTimers::new();
+        let now = Instant::now();
+        let id = timers.start(0, 10 * MS, Some(10 * MS));
+
+        assert_eq!(timers.expire(now + 55 * MS), vec![(id, 0)]);
+        let next = timers.next_deadline().unwrap();
+        assert!(next > now + 55 * MS && next <= now + 70 * MS);
+        assert!(timers.expire(now + 56 * MS).is_empty());
+    }
+
+    #[test]
+    fn a_zero_period_fires_once() {
+        let mut timers = TTimers::new();
+        let now = Instant::now();
+        let id = timers.start(0, Duration::ZERO, Some(Duration::ZERO));
+
+        assert_eq!(timers.expire(now + MS), vec![(id, 0)]);
+        assert!(timers.expire(now + 2 * MS).is_empty());
+        assert!(!timers.stop(id));
+    }
+}
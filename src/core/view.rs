@@ -1,7 +1,22 @@
+use crate::core::command::{CM_CANCEL, CM_CLOSE, CM_NO, CM_OK, CM_YES};
 use crate::core::rect::TRect;
 use crate::core::event::{TEvent, TEventQueue};
 use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::KeyEvent;
 
+/// Value a dialog control exchanges with the program, e.g. to fill a
+/// dialog before it runs and read the result after
+#[derive(Debug, Clone, PartialEq)]
+pub enum TData {
+    Text(String),
+    Index(usize),
+    Checked(Vec<bool>),
+    /// Values of a group's children that have data, in order
+    Record(Vec<TData>),
+}
+
+/// Something drawn on screen that takes events. Mouse events reach a view
+/// in the same coordinates as its `bounds`, those of the view that owns it.
 pub trait TView {
     fn draw(&self, buf: &mut ScreenBuffer, offset: (u16, u16));
     fn handle_event(&mut self, event: TEvent, queue: &TEventQueue);
@@ -9,4 +24,31 @@ pub trait TView {
     fn set_bounds(&mut self, bounds: TRect);
     fn set_focus(&mut self, _focused: bool);
     fn is_focusable(&self) -> bool { false }
+    /// Help context of this view, or of its focused child for containers
+    fn help_context(&self) -> Option<u16> { None }
+    /// True if this view handles `key` itself when focused, so its owner
+    /// should not apply its own meaning (e.g. Enter in a dialog)
+    fn takes_key(&self, _key: &KeyEvent) -> bool { false }
+    /// True for views that become the default button while focused
+    fn can_default(&self) -> bool { false }
+    /// True for the view pressed by Enter when no default-capable view has focus
+    fn is_default(&self) -> bool { false }
+    /// Shows whether this view is currently the one Enter presses
+    fn set_showing_default(&mut self, _showing: bool) {}
+    /// Letter that activates this view together with Alt, from a `~x~` label
+    fn hotkey(&self) -> Option<char> { None }
+    /// Checks the view's data before `command` takes effect, e.g. before a
+    /// dialog closes with `CM_OK` or the view loses focus (`CM_RELEASED_FOCUS`).
+    /// A view that is not valid posts `TEvent::Error` and returns false.
+    fn valid(&mut self, _command: u16, _queue: &TEventQueue) -> bool { true }
+    /// True if `command` posted while this view runs modally ends the modal
+    /// loop; other commands are sent to the view. By default these are the
+    /// commands that close a dialog.
+    fn ends_modal(&self, command: u16) -> bool {
+        matches!(command, CM_OK | CM_CANCEL | CM_YES | CM_NO | CM_CLOSE)
+    }
+    /// Current value of a dialog control, `None` for views without one
+    fn get_data(&self) -> Option<TData> { None }
+    /// Sets the value of a dialog control; values of the wrong kind are ignored
+    fn set_data(&mut self, _data: TData) {}
 }
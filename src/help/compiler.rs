@@ -0,0 +1,219 @@
+//! Compiles help source text into a [`THelpFile`].
+//!
+//! Source files are plain text split into topics:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! .topic Overview=1
+//! Welcome! See {the editor keys:EditorKeys} or {Shortcuts}.
+//!
+//! .topic EditorKeys=20
+//! Use {{ to write a literal brace.
+//! ```
+//!
+//! `.topic Name=N` starts a topic named `Name` with help context `N`; the
+//! `=N` part may be left out for topics only reached through links.
+//! `{text:Name}` links `text` to topic `Name`, and `{Name}` shows the topic
+//! name itself as the link text.
+
+use crate::help::helpfile::{HelpError, THelpFile, THelpLink, THelpTopic};
+
+/// A link found in one line of source text
+struct SourceLink {
+    col: usize,
+    len: usize,
+    target: String,
+}
+
+/// A link whose target has not been resolved yet
+struct PendingLink {
+    topic: usize,
+    link: THelpLink,
+    target: String,
+    source_line: usize,
+}
+
+pub fn compile(source: &str) -> Result<THelpFile, HelpError> {
+    let mut topics: Vec<THelpTopic> = Vec::new();
+    let mut pending: Vec<PendingLink> = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i + 1;
+
+        if let Some(header) = line.strip_prefix(".topic") {
+            if !header.is_empty() && !header.starts_with(char::is_whitespace) {
+                return Err(HelpError::new(line_no, "expected a space after .topic"));
+            }
+            let (name, context) = parse_topic_header(header.trim(), line_no)?;
+            if topics.iter().any(|topic| topic.name == name) {
+                return Err(HelpError::new(line_no, "duplicate topic name"));
+            }
+            if context.is_some() && topics.iter().any(|topic| topic.context == context) {
+                return Err(HelpError::new(line_no, "duplicate help context"));
+            }
+            trim_trailing_blank_lines(topics.last_mut());
+            topics.push(THelpTopic { name, context, lines: Vec::new(), links: Vec::new() });
+            continue;
+        }
+        if line.starts_with(';') {
+            continue;
+        }
+
+        let topic_index = match topics.len() {
+            0 if line.trim().is_empty() => continue,
+            0 => return Err(HelpError::new(line_no, "text before the first .topic")),
+            count => count - 1,
+        };
+        let topic = &mut topics[topic_index];
+
+        let (text, links) = parse_text_line(line, line_no)?;
+        let text_line = topic.lines.len();
+        for link in links {
+            pending.push(PendingLink {
+                topic: topic_index,
+                link: THelpLink { line: text_line, col: link.col, len: link.len, target: 0 },
+                target: link.target,
+                source_line: line_no,
+            });
+        }
+        topic.lines.push(text);
+    }
+    trim_trailing_blank_lines(topics.last_mut());
+
+    for mut pending_link in pending {
+        let target = topics
+            .iter()
+            .position(|topic| topic.name == pending_link.target)
+            .ok_or_else(|| HelpError::new(pending_link.source_line, &format!("unknown topic '{}'", pending_link.target)))?;
+        pending_link.link.target = target;
+        topics[pending_link.topic].links.push(pending_link.link);
+    }
+
+    Ok(THelpFile::new(topics))
+}
+
+fn parse_topic_header(header: &str, line_no: usize) -> Result<(String, Option<u16>), HelpError> {
+    let (name, context) = match header.split_once('=') {
+        Some((name, ctx)) => {
+            let ctx = ctx
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| HelpError::new(line_no, "bad help context"))?;
+            (name.trim(), Some(ctx))
+        }
+        None => (header, None),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(HelpError::new(line_no, "bad topic name"));
+    }
+    Ok((name.to_string(), context))
+}
+
+/// Strips link markup from a source line, returning the display text and
+/// the links in it
+fn parse_text_line(line: &str, line_no: usize) -> Result<(String, Vec<SourceLink>), HelpError> {
+    let mut text = String::new();
+    let mut col = 0;
+    let mut links = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+                col += 1;
+            }
+            '{' => {
+                let mut body = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => body.push(c),
+                        None => return Err(HelpError::new(line_no, "unterminated link")),
+                    }
+                }
+                let (label, target) = match body.rsplit_once(':') {
+                    Some((label, target)) => (label.to_string(), target.trim().to_string()),
+                    None => (body.clone(), body.trim().to_string()),
+                };
+                let len = label.chars().count();
+                if len == 0 || target.is_empty() {
+                    return Err(HelpError::new(line_no, "empty link"));
+                }
+                links.push(SourceLink { col, len, target });
+                text.push_str(&label);
+                col += len;
+            }
+            c => {
+                text.push(c);
+                col += 1;
+            }
+        }
+    }
+    Ok((text, links))
+}
+
+fn trim_trailing_blank_lines(topic: Option<&mut THelpTopic>) {
+    if let Some(topic) = topic {
+        while topic.lines.last().is_some_and(|line| line.trim().is_empty()) {
+            topic.lines.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+; Sample help
+.topic Overview=1
+Welcome! See {the editor keys:EditorKeys} or {Shortcuts}.
+
+.topic EditorKeys=20
+Use {{ to write a literal brace.
+Back to {Overview}.
+
+.topic Shortcuts
+F1 opens help.
+";
+
+    #[test]
+    fn compiles_topics_and_links() {
+        let file = compile(SOURCE).unwrap();
+        assert_eq!(file.topics.len(), 3);
+
+        let overview = &file.topics[0];
+        assert_eq!(overview.context, Some(1));
+        assert_eq!(overview.lines, vec!["Welcome! See the editor keys or Shortcuts."]);
+        assert_eq!(
+            overview.links,
+            vec![
+                THelpLink { line: 0, col: 13, len: 15, target: 1 },
+                THelpLink { line: 0, col: 32, len: 9, target: 2 },
+            ]
+        );
+
+        let keys = &file.topics[1];
+        assert_eq!(keys.lines, vec!["Use { to write a literal brace.", "Back to Overview."]);
+        assert_eq!(keys.links, vec![THelpLink { line: 1, col: 8, len: 8, target: 0 }]);
+        assert_eq!(file.topics[2].context, None);
+        assert_eq!(file.topic_for_context(20), Some(1));
+    }
+
+    #[test]
+    fn compiled_form_parses_back() {
+        let file = compile(SOURCE).unwrap();
+        assert_eq!(THelpFile::parse(&file.to_compiled_string()), Ok(file));
+    }
+
+    #[test]
+    fn reports_source_errors_with_line_numbers() {
+        assert_eq!(compile(".topicfoo").unwrap_err().line, 1);
+        assert_eq!(compile("; comment\ntext").unwrap_err().line, 2);
+        assert_eq!(compile(".topic A\n.topic A").unwrap_err().line, 2);
+        assert_eq!(compile(".topic A\n{unterminated").unwrap_err().line, 2);
+        assert_eq!(compile(".topic A\n\nSee {Missing}.").unwrap_err().line, 3);
+    }
+}
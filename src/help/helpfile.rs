@@ -0,0 +1,202 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// First line of every compiled help file
+const HEADER: &str = "RVHELP 1";
+
+/// A cross-reference inside a topic's text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct THelpLink {
+    pub line: usize,    // Line of the topic text holding the link
+    pub col: usize,     // Column of the first character, in chars
+    pub len: usize,     // Length of the link text, in chars
+    pub target: usize,  // Index of the target topic
+}
+
+/// One page of help text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct THelpTopic {
+    pub name: String,
+    pub context: Option<u16>,
+    pub lines: Vec<String>,
+    pub links: Vec<THelpLink>,
+}
+
+/// Error raised while reading a compiled help file or compiling help source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpError {
+    pub line: usize,    // 1-based line of the input where the error was found
+    pub message: String,
+}
+
+impl HelpError {
+    pub fn new(line: usize, message: &str) -> Self {
+        Self { line, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for HelpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for HelpError {}
+
+/// A compiled help file: topics with context IDs and resolved links.
+///
+/// The compiled form is plain text, one record per line:
+///
+/// ```text
+/// RVHELP 1
+/// TOPIC <context or -> <name>
+/// LINK <line> <col> <len> <target topic index>
+/// TEXT <line of topic text>
+/// ```
+///
+/// `LINK` and `TEXT` records belong to the preceding `TOPIC`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct THelpFile {
+    pub topics: Vec<THelpTopic>,
+}
+
+impl THelpFile {
+    pub fn new(topics: Vec<THelpTopic>) -> Self {
+        Self { topics }
+    }
+
+    /// Returns the index of the topic for help context `ctx`
+    pub fn topic_for_context(&self, ctx: u16) -> Option<usize> {
+        self.topics.iter().position(|topic| topic.context == Some(ctx))
+    }
+
+    pub fn topic(&self, index: usize) -> Option<&THelpTopic> {
+        self.topics.get(index)
+    }
+
+    /// Parses the compiled text form
+    pub fn parse(text: &str) -> Result<Self, HelpError> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(HelpError::new(1, "not a compiled help file")),
+        }
+
+        let mut topics: Vec<THelpTopic> = Vec::new();
+        // Topic, link and input line of each link record, checked once all text is read
+        let mut link_lines: Vec<(usize, usize, usize)> = Vec::new();
+        for (i, line) in lines {
+            let line_no = i + 1;
+            let (record, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match record {
+                "TOPIC" => {
+                    let (context, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let context = match context {
+                        "-" => None,
+                        ctx => Some(ctx.parse().map_err(|_| HelpError::new(line_no, "bad context ID"))?),
+                    };
+                    topics.push(THelpTopic {
+                        name: name.to_string(),
+                        context,
+                        lines: Vec::new(),
+                        links: Vec::new(),
+                    });
+                }
+                "LINK" | "TEXT" => {
+                    let Some(topic_index) = topics.len().checked_sub(1) else {
+                        return Err(HelpError::new(line_no, "record outside a topic"));
+                    };
+                    let topic = &mut topics[topic_index];
+                    if record == "TEXT" {
+                        topic.lines.push(rest.to_string());
+                        continue;
+                    }
+                    let fields = rest
+                        .split(' ')
+                        .map(|field| field.parse::<usize>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| HelpError::new(line_no, "bad link record"))?;
+                    let [line, col, len, target] = fields[..] else {
+                        return Err(HelpError::new(line_no, "bad link record"));
+                    };
+                    link_lines.push((topic_index, topic.links.len(), line_no));
+                    topic.links.push(THelpLink { line, col, len, target });
+                }
+                "" => {}
+                _ => return Err(HelpError::new(line_no, "unknown record")),
+            }
+        }
+
+        for (topic_index, link_index, line_no) in link_lines {
+            let topic = &topics[topic_index];
+            let link = &topic.links[link_index];
+            if link.target >= topics.len() {
+                return Err(HelpError::new(line_no, "link to a missing topic"));
+            }
+            let line_len = topic.lines.get(link.line).map(|text| text.chars().count());
+            if line_len.is_none_or(|line_len| link.len == 0 || link.col + link.len > line_len) {
+                return Err(HelpError::new(line_no, "link outside the topic text"));
+            }
+        }
+        Ok(Self { topics })
+    }
+
+    /// Returns the compiled text form
+    pub fn to_compiled_string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+        for topic in &self.topics {
+            let context = topic.context.map_or("-".to_string(), |ctx| ctx.to_string());
+            out.push_str(&format!("TOPIC {} {}\n", context, topic.name));
+            for link in &topic.links {
+                out.push_str(&format!("LINK {} {} {} {}\n", link.line, link.col, link.len, link.target));
+            }
+            for line in &topic.lines {
+                out.push_str(&format!("TEXT {}\n", line));
+            }
+        }
+        out
+    }
+
+    /// Loads a compiled help file from disk
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the compiled form to disk
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_compiled_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_a_missing_header() {
+        assert_eq!(THelpFile::parse("TOPIC - A\n").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn reports_bad_links_at_their_record() {
+        let missing = "RVHELP 1\nTOPIC 1 A\nLINK 0 0 1 5\nTEXT abc\n";
+        assert_eq!(THelpFile::parse(missing), Err(HelpError::new(3, "link to a missing topic")));
+
+        let past_line = "RVHELP 1\nTOPIC 1 A\nTEXT abc\nTOPIC - B\nLINK 0 2 2 0\nTEXT x\n";
+        assert_eq!(THelpFile::parse(past_line), Err(HelpError::new(5, "link outside the topic text")));
+
+        let no_line = "RVHELP 1\nTOPIC 1 A\nLINK 1 0 1 0\nTEXT abc\n";
+        assert_eq!(THelpFile::parse(no_line).unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn rejects_records_outside_a_topic() {
+        assert_eq!(THelpFile::parse("RVHELP 1\nTEXT abc\n").unwrap_err().line, 2);
+        assert_eq!(THelpFile::parse("RVHELP 1\nTOPIC x A\n").unwrap_err().line, 2);
+    }
+}
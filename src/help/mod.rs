@@ -0,0 +1,2 @@
+pub mod helpfile;
+pub mod compiler;
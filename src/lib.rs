@@ -1,3 +1,4 @@
 pub mod core;
 pub mod ui;
 pub mod widgets;
+pub mod help;
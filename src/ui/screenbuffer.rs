@@ -1,13 +1,18 @@
+use crate::core::rect::TRect;
+use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor, ResetColor};
+use crossterm::Command;
 use std::fmt::Write;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Cell {
     pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
 }
 
 impl Default for Cell {
     fn default() -> Self {
-        Self { ch: ' ' }
+        Self { ch: ' ', fg: Color::Reset, bg: Color::Reset }
     }
 }
 
@@ -15,6 +20,7 @@ pub struct ScreenBuffer {
     width: u16,
     height: u16,
     cells: Vec<Cell>,
+    shown: Vec<Cell>, // cells on the terminal after the last `flush_changes_to_string`
 }
 
 impl ScreenBuffer {
@@ -23,9 +29,18 @@ impl ScreenBuffer {
             width,
             height,
             cells: vec![Cell::default(); (width as usize) * (height as usize)],
+            shown: Vec::new(),
         }
     }
 
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
     fn index(&self, x: u16, y: u16) -> usize {
         (y as usize) * (self.width as usize) + (x as usize)
     }
@@ -34,6 +49,14 @@ impl ScreenBuffer {
         self.cells.fill(Cell::default());
     }
 
+    pub fn get(&self, x: u16, y: u16) -> Option<Cell> {
+        if x < self.width && y < self.height {
+            Some(self.cells[self.index(x, y)])
+        } else {
+            None
+        }
+    }
+
     pub fn set(&mut self, x: u16, y: u16, ch: char) {
         if x < self.width && y < self.height {
             let idx = self.index(x, y);
@@ -41,21 +64,153 @@ impl ScreenBuffer {
         }
     }
 
+    /// Sets character and colors of a single cell
+    pub fn set_styled(&mut self, x: u16, y: u16, ch: char, fg: Color, bg: Color) {
+        if x < self.width && y < self.height {
+            let idx = self.index(x, y);
+            self.cells[idx] = Cell { ch, fg, bg };
+        }
+    }
+
+    /// Changes the colors of a cell, keeping its character
+    pub fn set_colors(&mut self, x: u16, y: u16, fg: Color, bg: Color) {
+        if x < self.width && y < self.height {
+            let idx = self.index(x, y);
+            self.cells[idx].fg = fg;
+            self.cells[idx].bg = bg;
+        }
+    }
+
     pub fn write_str(&mut self, x: u16, y: u16, s: &str) {
         for (i, ch) in s.chars().enumerate() {
             self.set(x + i as u16, y, ch);
         }
     }
 
+    pub fn write_styled(&mut self, x: u16, y: u16, s: &str, fg: Color, bg: Color) {
+        for (i, ch) in s.chars().enumerate() {
+            self.set_styled(x + i as u16, y, ch, fg, bg);
+        }
+    }
+
+    /// Fills a rectangle with one character in the given colors
+    pub fn fill(&mut self, rect: TRect, ch: char, fg: Color, bg: Color) {
+        for y in rect.y..rect.y.saturating_add(rect.height) {
+            for x in rect.x..rect.x.saturating_add(rect.width) {
+                self.set_styled(x, y, ch, fg, bg);
+            }
+        }
+    }
+
+    /// Copies all of `source` here with its top-left cell at (`x`, `y`)
+    pub fn blit(&mut self, x: u16, y: u16, source: &ScreenBuffer) {
+        for row in 0..source.height {
+            for column in 0..source.width {
+                let cell = source.cells[source.index(column, row)];
+                self.set_styled(x.saturating_add(column), y.saturating_add(row), cell.ch, cell.fg, cell.bg);
+            }
+        }
+    }
+
+    /// Draws a single-line box frame around the edge of `rect`
+    pub fn draw_frame(&mut self, rect: TRect, fg: Color, bg: Color) {
+        if rect.width < 2 || rect.height < 2 {
+            return;
+        }
+        let right = rect.x + rect.width - 1;
+        let bottom = rect.y + rect.height - 1;
+
+        for x in rect.x + 1..right {
+            self.set_styled(x, rect.y, '─', fg, bg);
+            self.set_styled(x, bottom, '─', fg, bg);
+        }
+        for y in rect.y + 1..bottom {
+            self.set_styled(rect.x, y, '│', fg, bg);
+            self.set_styled(right, y, '│', fg, bg);
+        }
+        self.set_styled(rect.x, rect.y, '┌', fg, bg);
+        self.set_styled(right, rect.y, '┐', fg, bg);
+        self.set_styled(rect.x, bottom, '└', fg, bg);
+        self.set_styled(right, bottom, '┘', fg, bg);
+    }
+
+    /// Casts a TurboVision-style shadow: two columns to the right of `rect`
+    /// and one row below it, both offset so the view appears to float.
+    /// Shadowed cells keep their character but are drawn dark grey on black.
+    pub fn shadow(&mut self, rect: TRect) {
+        let right = rect.x.saturating_add(rect.width);
+        let bottom = rect.y.saturating_add(rect.height);
+
+        for y in rect.y + 1..=bottom {
+            self.set_colors(right, y, Color::DarkGrey, Color::Black);
+            self.set_colors(right + 1, y, Color::DarkGrey, Color::Black);
+        }
+        for x in rect.x + 2..right {
+            self.set_colors(x, bottom, Color::DarkGrey, Color::Black);
+        }
+    }
+
     pub fn flush_to_string(&self) -> String {
         let mut output = String::new();
         for y in 0..self.height {
             write!(&mut output, "\x1B[{};1H", y + 1).ok(); // Move cursor
+            let mut colors = None;
             for x in 0..self.width {
-                let ch = self.cells[self.index(x, y)].ch;
-                output.push(ch);
+                let cell = self.cells[self.index(x, y)];
+                if colors != Some((cell.fg, cell.bg)) {
+                    SetForegroundColor(cell.fg).write_ansi(&mut output).ok();
+                    SetBackgroundColor(cell.bg).write_ansi(&mut output).ok();
+                    colors = Some((cell.fg, cell.bg));
+                }
+                output.push(cell.ch);
             }
+            ResetColor.write_ansi(&mut output).ok();
         }
         output
     }
+
+    /// Returns the output that brings the terminal from the last call's
+    /// contents to this buffer's: only the cells that changed are written,
+    /// so an update such as a progress bar moving costs a few bytes rather
+    /// than a whole screen. The first call writes every cell.
+    pub fn flush_changes_to_string(&mut self) -> String {
+        if self.shown.len() != self.cells.len() {
+            self.shown = self.cells.clone();
+            return self.flush_to_string();
+        }
+        let mut output = String::new();
+        let mut colors = None;
+        for y in 0..self.height {
+            let mut in_run = false; // cursor is already at the next cell
+            for x in 0..self.width {
+                let idx = self.index(x, y);
+                let cell = self.cells[idx];
+                if cell == self.shown[idx] {
+                    in_run = false;
+                    continue;
+                }
+                if !in_run {
+                    write!(&mut output, "\x1B[{};{}H", y + 1, x + 1).ok();
+                    in_run = true;
+                }
+                if colors != Some((cell.fg, cell.bg)) {
+                    SetForegroundColor(cell.fg).write_ansi(&mut output).ok();
+                    SetBackgroundColor(cell.bg).write_ansi(&mut output).ok();
+                    colors = Some((cell.fg, cell.bg));
+                }
+                output.push(cell.ch);
+            }
+        }
+        if colors.is_some() {
+            ResetColor.write_ansi(&mut output).ok();
+        }
+        self.shown.copy_from_slice(&self.cells);
+        output
+    }
+
+    /// Makes the next `flush_changes_to_string` write every cell, e.g.
+    /// after the terminal was cleared
+    pub fn invalidate(&mut self) {
+        self.shown.clear();
+    }
 }
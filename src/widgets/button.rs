@@ -1,14 +1,42 @@
 use crate::core::event::{TEvent, TEventQueue};
-use crate::core::rect::TRect;
+use crate::core::menu::hotkey_of;
+use crate::core::rect::{TPoint, TRect};
+use crate::core::timer::TimerId;
 use crate::core::view::TView;
 use crate::ui::screenbuffer::ScreenBuffer;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+use std::time::{Duration, Instant};
 
+const BUTTON_BG: Color = Color::Green;
+const NORMAL_FG: Color = Color::Black;
+const FOCUSED_FG: Color = Color::White;
+const DEFAULT_FG: Color = Color::Cyan;
+const DISABLED_FG: Color = Color::DarkGrey;
+const HOTKEY_FG: Color = Color::Yellow;
+const SHADOW_FG: Color = Color::DarkGrey;
+
+/// Callback run when a button is pressed
+pub type PressCallback = Box<dyn FnMut(&TEventQueue)>;
+
+/// How long a pressed button is drawn pushed in
+const PRESS_DURATION: Duration = Duration::from_millis(150);
+
+/// A push button drawn TurboVision-style: a one-row face with a shadow on
+/// its right and, when the bounds are two rows high, below it.
 pub struct TButton {
-    pub label: String,
+    pub label: String,                   // Label text with ~hotkey~ markers, e.g. "~O~K"
     pub bounds: TRect,
     pub focused: bool,
-    pub on_press: Option<Box<dyn FnMut()>>,
+    pub help_context: Option<u16>,
+    pub command: Option<u16>,
+    pub is_default: bool,
+    pub showing_default: bool,
+    pub disabled: bool,
+    pub on_press: Option<PressCallback>,
+    pressed_at: Option<Instant>,
+    press_timer: Option<TimerId>, // Ends the press animation
+    mouse_down: bool,
 }
 
 impl TButton {
@@ -17,43 +45,169 @@ impl TButton {
             label: text.to_string(),
             bounds,
             focused: false,
+            help_context: None,
+            command: None,
+            is_default: false,
+            showing_default: false,
+            disabled: false,
             on_press: None,
+            pressed_at: None,
+            press_timer: None,
+            mouse_down: false,
         }
     }
 
+    /// Sets the command posted when the button is pressed
+    pub fn with_command(mut self, command: u16) -> Self {
+        self.command = Some(command);
+        self
+    }
+
+    /// Makes this the dialog's default button, pressed by Enter
+    pub fn with_default(mut self, default: bool) -> Self {
+        self.is_default = default;
+        self.showing_default = default;
+        self
+    }
+
+    pub fn disabled(mut self, val: bool) -> Self {
+        self.disabled = val;
+        self
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    /// Sets a callback run on press, before the command is posted
     pub fn set_callback<F>(&mut self, callback: F)
     where
-        F: FnMut() + 'static,
+        F: FnMut(&TEventQueue) + 'static,
     {
         self.on_press = Some(Box::new(callback));
     }
 
-    fn render_label(&self) -> String {
-        let content = format!("[{}]", self.label);
-        if content.len() >= self.bounds.width as usize {
-            content[..self.bounds.width as usize].to_string()
-        } else {
-            format!("{:<width$}", content, width = self.bounds.width as usize)
+    /// Runs the callback, posts the command and starts the press animation
+    pub fn press(&mut self, queue: &TEventQueue) {
+        if self.disabled {
+            return;
+        }
+        self.pressed_at = Some(Instant::now());
+        // The timer wakes the application to draw the button released,
+        // even if the event goes to another view after the command
+        if let Some(timer) = self.press_timer.replace(queue.set_timer(PRESS_DURATION)) {
+            queue.kill_timer(timer);
+        }
+        if let Some(callback) = &mut self.on_press {
+            callback(queue);
         }
+        if let Some(command) = self.command {
+            queue.put_event(TEvent::Command(command));
+        }
+    }
+
+    fn is_pressed(&self) -> bool {
+        self.mouse_down || self.pressed_at.is_some_and(|at| at.elapsed() < PRESS_DURATION)
+    }
+
+    fn label_width(&self) -> usize {
+        self.label.chars().filter(|&c| c != '~').count()
     }
 }
 
+/// Draws a half-block shadow character over whatever background is there
+fn shadow_char(buffer: &mut ScreenBuffer, x: u16, y: u16, ch: char) {
+    let bg = buffer.get(x, y).map_or(Color::Reset, |cell| cell.bg);
+    buffer.set_styled(x, y, ch, SHADOW_FG, bg);
+}
+
 impl TView for TButton {
     fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
         let x = offset.0 + self.bounds.x;
         let y = offset.1 + self.bounds.y;
+        let face_width = self.bounds.width.saturating_sub(1);
+        let pressed = self.is_pressed();
+
+        let fg = if self.disabled {
+            DISABLED_FG
+        } else if self.focused {
+            FOCUSED_FG
+        } else if self.showing_default {
+            DEFAULT_FG
+        } else {
+            NORMAL_FG
+        };
+
+        // A pressed button moves one column right, over its shadow
+        let face_x = if pressed { x + 1 } else { x };
+        if pressed {
+            buffer.set(x, y, ' ');
+        }
+        buffer.fill(TRect::new(face_x, y, face_width, 1), ' ', fg, BUTTON_BG);
+
+        if self.showing_default && face_width >= 2 {
+            buffer.set_styled(face_x, y, '»', fg, BUTTON_BG);
+            buffer.set_styled(face_x + face_width - 1, y, '«', fg, BUTTON_BG);
+        }
 
-        let content = self.render_label();
-        buffer.write_str(x, y, &content);
+        let mut cx = face_x + (face_width.saturating_sub(self.label_width() as u16)) / 2;
+        let mut in_hotkey = false;
+        for ch in self.label.chars() {
+            if ch == '~' {
+                in_hotkey = !in_hotkey;
+                continue;
+            }
+            if cx >= face_x + face_width {
+                break;
+            }
+            let ch_fg = if in_hotkey && !self.disabled { HOTKEY_FG } else { fg };
+            buffer.set_styled(cx, y, ch, ch_fg, BUTTON_BG);
+            cx += 1;
+        }
+
+        if !pressed && self.bounds.width > 0 {
+            shadow_char(buffer, x + face_width, y, '▄');
+            if self.bounds.height > 1 {
+                for sx in x + 1..=x + face_width {
+                    shadow_char(buffer, sx, y + 1, '▀');
+                }
+            }
+        }
     }
 
     fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
-        if let TEvent::Key(KeyEvent { code: KeyCode::Enter, .. })
-            | TEvent::Key(KeyEvent { code: KeyCode::Char(' '), .. }) = event
-        {
-            if let Some(callback) = &mut self.on_press {
-                callback();
+        if self.disabled {
+            return;
+        }
+        match event {
+            TEvent::Key(KeyEvent { code: KeyCode::Enter, .. })
+            | TEvent::Key(KeyEvent { code: KeyCode::Char(' '), .. }) => self.press(queue),
+            TEvent::Key(KeyEvent { code: KeyCode::Char(c), modifiers, .. })
+                if modifiers.contains(KeyModifiers::ALT) && self.hotkey() == Some(c.to_ascii_lowercase()) =>
+            {
+                self.press(queue);
+            }
+            TEvent::Mouse(mouse) => {
+                let inside = self.bounds.contains(TPoint { x: mouse.column, y: mouse.row });
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => self.mouse_down = inside,
+                    MouseEventKind::Drag(MouseButton::Left) if self.mouse_down && !inside => {
+                        self.mouse_down = false;
+                    }
+                    MouseEventKind::Up(MouseButton::Left) => {
+                        if self.mouse_down && inside {
+                            self.press(queue);
+                        }
+                        self.mouse_down = false;
+                    }
+                    _ => {}
+                }
+            }
+            TEvent::Timer(id) if self.press_timer == Some(id) => {
+                self.press_timer = None;
+                self.pressed_at = None;
             }
+            _ => {}
         }
     }
 
@@ -69,6 +223,112 @@ impl TView for TButton {
         self.focused = focused;
     }
     fn is_focusable(&self) -> bool {
-        true
-    }    
+        !self.disabled
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Enter | KeyCode::Char(' '))
+    }
+
+    fn can_default(&self) -> bool {
+        !self.disabled
+    }
+
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    fn set_showing_default(&mut self, showing: bool) {
+        self.showing_default = showing;
+    }
+
+    fn hotkey(&self) -> Option<char> {
+        hotkey_of(&self.label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::MouseEvent;
+
+    fn ok_button() -> TButton {
+        TButton::new("~O~K", TRect::new(2, 2, 10, 2)).with_command(42)
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> TEvent {
+        TEvent::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> TEvent {
+        TEvent::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })
+    }
+
+    fn command(queue: &TEventQueue) -> Option<u16> {
+        match queue.get_event() {
+            Some(TEvent::Command(command)) => Some(command),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn keys_press_button() {
+        let queue = TEventQueue::new();
+        let mut button = ok_button();
+        for event in [key(KeyCode::Enter, KeyModifiers::NONE), key(KeyCode::Char(' '), KeyModifiers::NONE), key(KeyCode::Char('o'), KeyModifiers::ALT)] {
+            button.handle_event(event, &queue);
+            assert_eq!(command(&queue), Some(42));
+        }
+        button.handle_event(key(KeyCode::Char('x'), KeyModifiers::ALT), &queue);
+        assert_eq!(command(&queue), None);
+    }
+
+    #[test]
+    fn disabled_button_ignores_presses() {
+        let queue = TEventQueue::new();
+        let mut button = ok_button().disabled(true);
+        button.handle_event(key(KeyCode::Enter, KeyModifiers::NONE), &queue);
+        button.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 3, 2), &queue);
+        button.handle_event(mouse(MouseEventKind::Up(MouseButton::Left), 3, 2), &queue);
+        button.press(&queue);
+        assert_eq!(command(&queue), None);
+        assert!(!button.is_focusable() && !button.can_default());
+    }
+
+    #[test]
+    fn click_presses_only_if_released_inside() {
+        let queue = TEventQueue::new();
+        let mut button = ok_button();
+        button.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 3, 2), &queue);
+        assert!(button.is_pressed());
+        button.handle_event(mouse(MouseEventKind::Up(MouseButton::Left), 4, 2), &queue);
+        assert_eq!(command(&queue), Some(42));
+
+        // Dragging off the button lets it go without a press
+        let mut button = ok_button();
+        button.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 3, 2), &queue);
+        button.handle_event(mouse(MouseEventKind::Drag(MouseButton::Left), 30, 2), &queue);
+        button.handle_event(mouse(MouseEventKind::Up(MouseButton::Left), 3, 2), &queue);
+        assert_eq!(command(&queue), None);
+    }
+
+    #[test]
+    fn press_timer_releases_button() {
+        let queue = TEventQueue::new();
+        let mut button = ok_button();
+        button.press(&queue);
+        assert!(button.is_pressed());
+        let timer = button.press_timer.expect("press starts a timer");
+
+        // Another timer leaves the button pressed
+        button.handle_event(TEvent::Timer(timer + 1), &queue);
+        assert!(button.is_pressed());
+        button.handle_event(TEvent::Timer(timer), &queue);
+        assert!(!button.is_pressed());
+        assert_eq!(button.press_timer, None);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::core::rect::TRect;
 use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::view::{TData, TView};
 use crate::ui::screenbuffer::ScreenBuffer;
 use crossterm::event::{KeyCode, KeyEvent};
 
@@ -9,6 +9,7 @@ pub struct TCheckBoxes {
     pub bounds: TRect,
     pub selected: usize,
     pub focused: bool,
+    pub help_context: Option<u16>,
 }
 
 impl TCheckBoxes {
@@ -18,9 +19,14 @@ impl TCheckBoxes {
             bounds,
             selected: 0,
             focused: true,
+            help_context: None,
         }
     }
 
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
     pub fn is_checked(&self, index: usize) -> bool {
         self.items
             .get(index)
@@ -44,18 +50,14 @@ impl TView for TCheckBoxes {
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+    fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
         if let TEvent::Key(KeyEvent { code, .. }) = event {
             match code {
-                KeyCode::Up => {
-                    if self.selected > 0 {
-                        self.selected -= 1;
-                    }
+                KeyCode::Up if self.selected > 0 => {
+                    self.selected -= 1;
                 }
-                KeyCode::Down => {
-                    if self.selected + 1 < self.items.len() {
-                        self.selected += 1;
-                    }
+                KeyCode::Down if self.selected + 1 < self.items.len() => {
+                    self.selected += 1;
                 }
                 KeyCode::Enter | KeyCode::Char(' ') => {
                     if let Some((_, checked)) = self.items.get_mut(self.selected) {
@@ -79,4 +81,19 @@ impl TView for TCheckBoxes {
         self.focused = focused;
     }
 
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    fn get_data(&self) -> Option<TData> {
+        Some(TData::Checked(self.items.iter().map(|(_, checked)| *checked).collect()))
+    }
+
+    fn set_data(&mut self, data: TData) {
+        if let TData::Checked(values) = data {
+            for ((_, checked), value) in self.items.iter_mut().zip(values) {
+                *checked = value;
+            }
+        }
+    }
 }
@@ -0,0 +1,329 @@
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::view::{TData, TView};
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::inputline::TInputLine;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+use std::cell::Cell;
+
+const FIELD_FG: Color = Color::White;
+const FIELD_BG: Color = Color::Blue;
+const SELECTED_FG: Color = Color::Black;
+const SELECTED_BG: Color = Color::Cyan;
+const ICON_FG: Color = Color::Green;
+const ARROW_FG: Color = Color::Black;
+const ARROW_BG: Color = Color::Green;
+
+/// Width of the `▐▼▌` button drawn after the value field
+const BUTTON_WIDTH: u16 = 3;
+
+/// A one-line field showing the value picked from a list of items. The
+/// list drops down below the field on Alt+Down or a click on the `▼`
+/// button. Without editing, Up and Down step through the items and typing
+/// a letter jumps to the next item starting with it; a click anywhere on
+/// the field opens the list. In editable mode the field is an input line,
+/// any text may be entered, and the list opens on the items starting with
+/// the text typed. Typing in the open list narrows it further.
+pub struct TComboBox {
+    pub input: TInputLine,
+    pub items: Vec<String>,
+    pub editable: bool,
+    selected: Option<usize>,
+    shown: Vec<usize>,         // Items in the open list, to map its choice back
+    origin: Cell<(u16, u16)>,  // screen position of the last draw
+}
+
+impl TComboBox {
+    /// Makes a combo box showing the first item, its button included in
+    /// `bounds`
+    pub fn new(bounds: TRect, items: Vec<&str>) -> Self {
+        let width = bounds.width.saturating_sub(BUTTON_WIDTH);
+        let mut combo = Self {
+            input: TInputLine::new(TRect::new(bounds.x, bounds.y, width, 1)),
+            items: items.into_iter().map(String::from).collect(),
+            editable: false,
+            selected: None,
+            shown: Vec::new(),
+            origin: Cell::new((0, 0)),
+        };
+        combo.select(0);
+        combo
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.input.set_help_context(ctx);
+    }
+
+    /// Lets any text be typed into the field, not only the items
+    pub fn set_editable(&mut self, editable: bool) {
+        self.editable = editable;
+    }
+
+    /// Replaces the items, keeping the value if it is still one of them
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.sync_selected();
+        if !self.editable && self.selected.is_none() {
+            self.select(0);
+        }
+    }
+
+    /// Index of the item shown, `None` if the text typed in editable mode
+    /// is not one of the items
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn get_value(&self) -> &str {
+        self.input.get_value()
+    }
+
+    /// Shows the item at `index`, if there is one
+    pub fn select(&mut self, index: usize) {
+        if let Some(item) = self.items.get(index) {
+            self.input.set_value(item);
+            self.input.select_all();
+            self.selected = Some(index);
+        }
+    }
+
+    /// Sets the text in editable mode; otherwise selects the item with
+    /// that text, if any
+    pub fn set_value(&mut self, value: &str) {
+        if self.editable {
+            self.input.set_value(value);
+            self.sync_selected();
+        } else if let Some(index) = self.items.iter().position(|item| item == value) {
+            self.select(index);
+        }
+    }
+
+    /// Finds the item matching the field's text
+    fn sync_selected(&mut self) {
+        let value = self.input.get_value();
+        self.selected = self.items.iter().position(|item| item == value);
+    }
+
+    fn button_bounds(&self) -> TRect {
+        let input = self.input.bounds;
+        TRect::new(input.x + input.width, input.y, BUTTON_WIDTH, 1)
+    }
+
+    /// Items to list: in editable mode those starting with the text typed,
+    /// unless the text is a whole item or nothing matches
+    fn filtered(&self) -> Vec<usize> {
+        let all = || (0..self.items.len()).collect();
+        if !self.editable || self.selected.is_some() {
+            return all();
+        }
+        let typed = self.input.get_value().to_lowercase();
+        let matching: Vec<usize> = (0..self.items.len())
+            .filter(|&index| self.items[index].to_lowercase().starts_with(&typed))
+            .collect();
+        if matching.is_empty() { all() } else { matching }
+    }
+
+    /// Asks the application to show the list below the field
+    fn open_list(&mut self, queue: &TEventQueue) {
+        self.shown = self.filtered();
+        if self.shown.is_empty() {
+            return;
+        }
+        let entries = self.shown.iter().map(|&index| self.items[index].clone()).collect();
+        let (x, y) = self.origin.get();
+        let at = TRect::new(x, y, self.input.bounds.width + BUTTON_WIDTH, 1);
+        let selected = self.selected.and_then(|selected| self.shown.iter().position(|&index| index == selected));
+        queue.put_event(TEvent::PopupList(entries, at, selected.unwrap_or(0)));
+    }
+
+    /// Selects the next item after the current one starting with `ch`
+    fn jump_to(&mut self, ch: char) {
+        let ch = ch.to_lowercase().next().unwrap_or(ch);
+        let count = self.items.len();
+        let start = self.selected.map_or(0, |index| index + 1);
+        let found = (0..count)
+            .map(|step| (start + step) % count)
+            .find(|&index| self.items[index].to_lowercase().starts_with(ch));
+        if let Some(index) = found {
+            self.select(index);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, queue: &TEventQueue) {
+        if key.code == KeyCode::Down && key.modifiers.contains(KeyModifiers::ALT) {
+            self.open_list(queue);
+            return;
+        }
+        if self.editable {
+            self.input.handle_event(TEvent::Key(key), queue);
+            self.sync_selected();
+            return;
+        }
+        let last = self.items.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Up => self.select(self.selected.unwrap_or(0).saturating_sub(1)),
+            KeyCode::Down => self.select(self.selected.map_or(0, |index| (index + 1).min(last))),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(last),
+            KeyCode::Char(ch) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => self.jump_to(ch),
+            _ => {}
+        }
+    }
+}
+
+impl TView for TComboBox {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        self.origin.set((offset.0 + self.input.bounds.x, offset.1 + self.input.bounds.y));
+        if self.editable {
+            self.input.draw(buffer, offset);
+        } else {
+            let field = self.input.bounds;
+            let (fg, bg) = if self.input.focused { (SELECTED_FG, SELECTED_BG) } else { (FIELD_FG, FIELD_BG) };
+            let x = offset.0 + field.x;
+            let y = offset.1 + field.y;
+            buffer.fill(TRect::new(x, y, field.width, 1), ' ', FIELD_FG, FIELD_BG);
+            let text: String = self.input.get_value().chars().take(field.width.saturating_sub(2) as usize).collect();
+            if !text.is_empty() {
+                buffer.write_styled(x + 1, y, &text, fg, bg);
+            }
+        }
+
+        let button = self.button_bounds();
+        let x = offset.0 + button.x;
+        let y = offset.1 + button.y;
+        let bg = buffer.get(x, y).map_or(Color::Reset, |cell| cell.bg);
+        buffer.set_styled(x, y, '▐', ICON_FG, bg);
+        buffer.set_styled(x + 1, y, '▼', ARROW_FG, ARROW_BG);
+        buffer.set_styled(x + 2, y, '▌', ICON_FG, bg);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Key(key) => self.handle_key(key, queue),
+            TEvent::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                if self.button_bounds().contains(pt) || (!self.editable && self.input.bounds.contains(pt)) {
+                    self.open_list(queue);
+                } else if self.editable {
+                    self.input.handle_event(TEvent::Mouse(mouse), queue);
+                }
+            }
+            TEvent::Mouse(mouse) if self.editable => self.input.handle_event(TEvent::Mouse(mouse), queue),
+            TEvent::ListChoice(index) => {
+                if let Some(&index) = self.shown.get(index) {
+                    self.select(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        let input = self.input.bounds;
+        TRect::new(input.x, input.y, input.width + BUTTON_WIDTH, input.height)
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        let width = bounds.width.saturating_sub(BUTTON_WIDTH);
+        self.input.set_bounds(TRect::new(bounds.x, bounds.y, width, 1));
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.input.set_focus(focused);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.input.help_context()
+    }
+
+    /// The text in editable mode, otherwise the index of the item shown.
+    /// Without editing an item is always shown unless there are none; the
+    /// index is then 0, so the box still has a place in its dialog's record.
+    fn get_data(&self) -> Option<TData> {
+        if self.editable {
+            Some(TData::Text(self.input.get_value().to_string()))
+        } else {
+            Some(TData::Index(self.selected.unwrap_or(0)))
+        }
+    }
+
+    fn set_data(&mut self, data: TData) {
+        match data {
+            TData::Index(index) => self.select(index),
+            TData::Text(text) => self.set_value(&text),
+            _ => {}
+        }
+    }
+
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        !self.editable || self.input.valid(command, queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> TEvent {
+        TEvent::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn combo() -> TComboBox {
+        TComboBox::new(TRect::new(0, 0, 20, 1), vec!["Red", "Green", "Blue", "Black"])
+    }
+
+    /// Opens the list with Alt+Down and returns the items and selection it asks for
+    fn open(combo: &mut TComboBox, queue: &TEventQueue) -> (Vec<String>, usize) {
+        combo.handle_event(key(KeyCode::Down, KeyModifiers::ALT), queue);
+        match queue.get_event() {
+            Some(TEvent::PopupList(items, _, selected)) => (items, selected),
+            other => panic!("expected a popup list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn drop_down_opens_on_current_item() {
+        let queue = TEventQueue::new();
+        let mut combo = combo();
+        combo.handle_event(key(KeyCode::Down, KeyModifiers::NONE), &queue);
+        assert_eq!(open(&mut combo, &queue), (vec!["Red".into(), "Green".into(), "Blue".into(), "Black".into()], 1));
+
+        combo.handle_event(TEvent::ListChoice(3), &queue);
+        assert_eq!(combo.get_value(), "Black");
+        assert_eq!(combo.get_data(), Some(TData::Index(3)));
+    }
+
+    #[test]
+    fn editable_list_shows_items_starting_with_text() {
+        let queue = TEventQueue::new();
+        let mut combo = combo();
+        combo.set_editable(true);
+        combo.set_value("bl");
+        assert_eq!(combo.selected_index(), None);
+        assert_eq!(open(&mut combo, &queue), (vec!["Blue".into(), "Black".into()], 0));
+
+        // The choice is an index into the filtered list
+        combo.handle_event(TEvent::ListChoice(1), &queue);
+        assert_eq!(combo.get_value(), "Black");
+        assert_eq!(combo.selected_index(), Some(3));
+
+        // Text matching nothing lists every item
+        combo.set_value("x");
+        assert_eq!(open(&mut combo, &queue).0.len(), 4);
+        assert_eq!(combo.get_data(), Some(TData::Text("x".into())));
+    }
+
+    #[test]
+    fn data_without_items_is_index_zero() {
+        let mut combo = TComboBox::new(TRect::new(0, 0, 20, 1), Vec::new());
+        assert_eq!(combo.selected_index(), None);
+        assert_eq!(combo.get_data(), Some(TData::Index(0)));
+        combo.set_items(vec!["One".into()]);
+        assert_eq!(combo.get_value(), "One");
+    }
+}
@@ -1,15 +1,18 @@
-use crate::core::rect::TRect;
-use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::command::{CM_CANCEL, CM_OK, CM_RELEASED_FOCUS};
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{mouse_to_local, TEvent, TEventQueue};
+use crate::core::view::{TData, TView};
 use crate::core::focus::FocusManager;
 use crate::ui::screenbuffer::ScreenBuffer;
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
 
 pub struct TDialog {
     pub title: String,
     pub bounds: TRect,
     pub children: Vec<Box<dyn TView>>,
     pub focus: FocusManager,
+    mouse_target: Option<usize>, // child that got the last button press
 }
 
 impl TDialog {
@@ -19,6 +22,7 @@ impl TDialog {
             bounds,
             children: Vec::new(),
             focus: FocusManager::new(),
+            mouse_target: None,
         }
     }
 
@@ -27,6 +31,77 @@ impl TDialog {
             child.set_focus(true);
         }
         self.children.push(child);
+        self.update_default();
+    }
+
+    /// Index of the button pressed by Enter: the focused button if there is
+    /// one, otherwise the button marked as default
+    fn default_index(&self) -> Option<usize> {
+        let focused = self.focus.index;
+        if self.children.get(focused).is_some_and(|child| child.can_default()) {
+            return Some(focused);
+        }
+        self.children.iter().position(|child| child.is_default())
+    }
+
+    /// Focuses the child at `index` if it can take focus and the focused
+    /// child's data is valid. Returns true if `index` has focus afterwards.
+    fn focus_child(&mut self, index: usize, queue: &TEventQueue) -> bool {
+        if index != self.focus.index {
+            if !self.release_focus(queue) {
+                return false;
+            }
+            self.focus.set_focus(&mut self.children, index);
+            self.update_default();
+        }
+        self.focus.index == index
+    }
+
+    /// Asks the focused child whether it may lose focus
+    fn release_focus(&mut self, queue: &TEventQueue) -> bool {
+        self.focus
+            .current_mut(&mut self.children)
+            .is_none_or(|child| child.valid(CM_RELEASED_FOCUS, queue))
+    }
+
+    /// Sends a mouse event to the child under the pointer, in the child's
+    /// coordinates. A child that got a button press keeps receiving events
+    /// until the button is released.
+    fn route_mouse(&mut self, mouse: MouseEvent, queue: &TEventQueue) {
+        let local = mouse_to_local(mouse, (self.bounds.x + 1, self.bounds.y + 1));
+        let pt = TPoint { x: local.column, y: local.row };
+
+        let target = match mouse.kind {
+            MouseEventKind::Down(_) => {
+                let mut hit = self.children.iter().rposition(|child| child.get_bounds().contains(pt));
+                if let Some(index) = hit {
+                    if self.children[index].is_focusable() && !self.focus_child(index, queue) {
+                        hit = None;
+                    }
+                }
+                self.mouse_target = hit;
+                hit
+            }
+            MouseEventKind::Drag(_) | MouseEventKind::Up(_) => self.mouse_target,
+            _ => self.children.iter().rposition(|child| child.get_bounds().contains(pt)),
+        };
+        if let MouseEventKind::Up(_) = mouse.kind {
+            self.mouse_target = None;
+        }
+
+        if let Some(index) = target {
+            self.children[index].handle_event(TEvent::Mouse(local), queue);
+        }
+    }
+
+    /// Moves the default highlight to the button Enter would press
+    fn update_default(&mut self) {
+        let default = self.default_index();
+        for (i, child) in self.children.iter_mut().enumerate() {
+            if child.can_default() {
+                child.set_showing_default(Some(i) == default);
+            }
+        }
     }
 
     fn draw_border(&self, buffer: &mut ScreenBuffer) {
@@ -51,6 +126,7 @@ impl TDialog {
 
 impl TView for TDialog {
     fn draw(&self, buffer: &mut ScreenBuffer, _offset: (u16, u16)) {
+        buffer.fill(self.bounds, ' ', Color::Reset, Color::Reset);
         self.draw_border(buffer);
 
         let offset = (self.bounds.x + 1, self.bounds.y + 1);
@@ -60,16 +136,57 @@ impl TView for TDialog {
     }
 
     fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        if let TEvent::Mouse(mouse) = event {
+            self.route_mouse(mouse, queue);
+            return;
+        }
+        // Modal loops check the dialog before it closes; a dialog outside one
+        // gets OK itself and stops it here if a child is not valid
+        if let TEvent::Command(CM_OK) = event {
+            if !self.valid(CM_OK, queue) {
+                return;
+            }
+        }
+
         if let TEvent::Key(key) = &event {
+            let focused_takes_key = self
+                .focus
+                .current(&self.children)
+                .is_some_and(|child| child.takes_key(key));
+
             match key.code {
+                // Containers such as tab views move focus among their own views first
+                KeyCode::Tab | KeyCode::BackTab if focused_takes_key => {}
+                KeyCode::Tab | KeyCode::BackTab if !self.release_focus(queue) => return,
                 KeyCode::Tab => {
                     self.focus.focus_next(&mut self.children);
+                    self.update_default();
                     return;
                 }
                 KeyCode::BackTab => {
                     self.focus.focus_prev(&mut self.children);
+                    self.update_default();
+                    return;
+                }
+                KeyCode::Enter if !focused_takes_key => {
+                    if let Some(index) = self.default_index() {
+                        self.children[index].handle_event(event, queue);
+                    }
                     return;
                 }
+                KeyCode::Esc if !focused_takes_key => {
+                    queue.put_event(TEvent::Command(CM_CANCEL));
+                    return;
+                }
+                KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => {
+                    let hotkey = c.to_ascii_lowercase();
+                    if let Some(index) = self.children.iter().position(|child| child.hotkey() == Some(hotkey)) {
+                        if !self.children[index].is_focusable() || self.focus_child(index, queue) {
+                            self.children[index].handle_event(event, queue);
+                        }
+                        return;
+                    }
+                }
                 _ => {}
             }
         }
@@ -96,4 +213,185 @@ impl TView for TDialog {
     fn is_focusable(&self) -> bool {
         true
     }
+
+    fn help_context(&self) -> Option<u16> {
+        self.focus.current(&self.children).and_then(|child| child.help_context())
+    }
+
+    /// Collects the data of the children that have any, in order
+    fn get_data(&self) -> Option<TData> {
+        Some(TData::Record(self.children.iter().filter_map(|child| child.get_data()).collect()))
+    }
+
+    /// Hands out the values of a `TData::Record` to the children that have
+    /// data, in the order `get_data` returns them
+    fn set_data(&mut self, data: TData) {
+        if let TData::Record(values) = data {
+            let children = self.children.iter_mut().filter(|child| child.get_data().is_some());
+            for (child, value) in children.zip(values) {
+                child.set_data(value);
+            }
+        }
+    }
+
+    /// Checks every child, focusing the first one that is not valid.
+    /// Only the focused child is asked when the dialog itself loses focus.
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        if command == CM_RELEASED_FOCUS {
+            return self.release_focus(queue);
+        }
+        match self.children.iter_mut().position(|child| !child.valid(command, queue)) {
+            Some(index) => {
+                self.focus.set_focus(&mut self.children, index);
+                self.update_default();
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::button::TButton;
+    use crossterm::event::KeyEvent;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Focusable control that records the keys it gets
+    struct Field {
+        bounds: TRect,
+        takes_enter: bool,
+        valid: bool,
+        keys: Rc<RefCell<Vec<KeyCode>>>,
+    }
+
+    impl TView for Field {
+        fn draw(&self, _buffer: &mut ScreenBuffer, _offset: (u16, u16)) {}
+
+        fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
+            if let TEvent::Key(key) = event {
+                self.keys.borrow_mut().push(key.code);
+            }
+        }
+
+        fn get_bounds(&self) -> TRect {
+            self.bounds
+        }
+
+        fn set_bounds(&mut self, bounds: TRect) {
+            self.bounds = bounds;
+        }
+
+        fn set_focus(&mut self, _focused: bool) {}
+
+        fn is_focusable(&self) -> bool {
+            true
+        }
+
+        fn takes_key(&self, key: &KeyEvent) -> bool {
+            self.takes_enter && key.code == KeyCode::Enter
+        }
+
+        fn valid(&mut self, _command: u16, _queue: &TEventQueue) -> bool {
+            self.valid
+        }
+    }
+
+    const FIELD: usize = 0;
+    const CANCEL: usize = 2;
+
+    /// Dialog with a field, which has the focus, an OK button that is the
+    /// default and a Cancel button
+    fn dialog(takes_enter: bool, valid: bool) -> (TDialog, Rc<RefCell<Vec<KeyCode>>>) {
+        let keys = Rc::new(RefCell::new(Vec::new()));
+        let mut dialog = TDialog::new("Test", TRect::new(0, 0, 40, 10));
+        let field = Field { bounds: TRect::new(1, 1, 20, 1), takes_enter, valid, keys: Rc::clone(&keys) };
+        dialog.add_child(Box::new(field));
+        dialog.add_child(Box::new(TButton::new("~O~K", TRect::new(1, 5, 10, 2)).with_command(CM_OK).with_default(true)));
+        dialog.add_child(Box::new(TButton::new("~C~ancel", TRect::new(14, 5, 10, 2)).with_command(CM_CANCEL)));
+        (dialog, keys)
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> TEvent {
+        TEvent::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn commands(queue: &TEventQueue) -> Vec<u16> {
+        let mut commands = Vec::new();
+        while let Some(event) = queue.get_event() {
+            if let TEvent::Command(command) = event {
+                commands.push(command);
+            }
+        }
+        commands
+    }
+
+    #[test]
+    fn enter_presses_default_button() {
+        let queue = TEventQueue::new();
+        let (mut dialog, keys) = dialog(false, true);
+        dialog.handle_event(key(KeyCode::Enter, KeyModifiers::NONE), &queue);
+        assert_eq!(commands(&queue), vec![CM_OK]);
+        assert!(keys.borrow().is_empty());
+    }
+
+    #[test]
+    fn enter_goes_to_focused_control_that_takes_it() {
+        let queue = TEventQueue::new();
+        let (mut dialog, keys) = dialog(true, true);
+        dialog.handle_event(key(KeyCode::Enter, KeyModifiers::NONE), &queue);
+        assert!(commands(&queue).is_empty());
+        assert_eq!(*keys.borrow(), vec![KeyCode::Enter]);
+    }
+
+    #[test]
+    fn esc_posts_cancel() {
+        let queue = TEventQueue::new();
+        let (mut dialog, _) = dialog(false, true);
+        dialog.handle_event(key(KeyCode::Esc, KeyModifiers::NONE), &queue);
+        assert_eq!(commands(&queue), vec![CM_CANCEL]);
+    }
+
+    #[test]
+    fn alt_hotkey_focuses_and_presses_button() {
+        let queue = TEventQueue::new();
+        let (mut dialog, _) = dialog(false, true);
+        dialog.handle_event(key(KeyCode::Char('c'), KeyModifiers::ALT), &queue);
+        assert_eq!(dialog.focus.index, CANCEL);
+        assert_eq!(commands(&queue), vec![CM_CANCEL]);
+
+        // Upper case, as sent with Shift or Caps Lock, works the same
+        dialog.handle_event(key(KeyCode::Char('O'), KeyModifiers::ALT | KeyModifiers::SHIFT), &queue);
+        assert_eq!(commands(&queue), vec![CM_OK]);
+    }
+
+    #[test]
+    fn invalid_control_keeps_focus_and_blocks_ok() {
+        let queue = TEventQueue::new();
+        let (mut dialog, _) = dialog(false, false);
+        dialog.handle_event(key(KeyCode::Char('c'), KeyModifiers::ALT), &queue);
+        assert_eq!(dialog.focus.index, FIELD);
+        assert!(commands(&queue).is_empty());
+
+        dialog.handle_event(key(KeyCode::Tab, KeyModifiers::NONE), &queue);
+        assert_eq!(dialog.focus.index, FIELD);
+        assert!(!dialog.valid(CM_OK, &queue));
+    }
+
+    #[test]
+    fn valid_focuses_first_invalid_control() {
+        let queue = TEventQueue::new();
+        let (mut dialog, _) = dialog(false, true);
+        dialog.handle_event(key(KeyCode::Tab, KeyModifiers::NONE), &queue);
+        dialog.children[FIELD] = Box::new(Field {
+            bounds: TRect::new(1, 1, 20, 1),
+            takes_enter: false,
+            valid: false,
+            keys: Rc::new(RefCell::new(Vec::new())),
+        });
+        assert!(!dialog.valid(CM_OK, &queue));
+        assert_eq!(dialog.focus.index, FIELD);
+    }
 }
@@ -1,25 +1,100 @@
-use crate::core::rect::TRect;
+use crate::core::command::CM_CANCEL;
+use crate::core::rect::{TPoint, TRect};
 use crate::core::view::TView;
 use crate::core::event::{TEvent, TEventQueue};
 use crate::ui::screenbuffer::ScreenBuffer;
-use crate::widgets::menu::*;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::core::menu::*;
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
+use crossterm::style::Color;
 
+const MENU_FG: Color = Color::Black;
+const MENU_BG: Color = Color::Grey;
+const SELECTED_BG: Color = Color::Green;
+const DISABLED_FG: Color = Color::DarkGrey;
+const HOTKEY_FG: Color = Color::Red;
+
+/// Outcome of an event passed to one level of a menu
+#[derive(PartialEq, Eq)]
+enum MenuAction {
+    Handled,
+    Unhandled,
+    /// The menu at this level wants to be closed
+    Close,
+}
+
+/// A framed, vertical menu. Its `bounds` are screen coordinates, so it
+/// should be drawn at offset (0, 0).
 pub struct TDropDownMenu {
     pub menu: TMenu,
     pub bounds: TRect,
     selected_index: usize,
     focused: bool,
+    submenu: Option<Box<TDropDownMenu>>,
+    screen_size: (u16, u16),
 }
 
 impl TDropDownMenu {
+    /// Creates a drop-down whose top-left corner is at `bounds.x`, `bounds.y`.
+    /// The width and height are computed from the menu items.
     pub fn new(menu: TMenu, bounds: TRect) -> Self {
-        Self {
+        let (width, height) = Self::menu_size(&menu);
+        let mut dropdown = Self {
             menu,
-            bounds,
+            bounds: TRect::new(bounds.x, bounds.y, width, height),
             selected_index: 0,
             focused: false,
+            submenu: None,
+            screen_size: (u16::MAX, u16::MAX),
+        };
+        if dropdown.menu.items.first().is_some_and(|item| item.disabled) {
+            dropdown.move_down();
+        }
+        dropdown
+    }
+
+    /// Returns the frame size needed to show every item of `menu`
+    pub fn menu_size(menu: &TMenu) -> (u16, u16) {
+        let label_width = menu
+            .items
+            .iter()
+            .map(|item| item.clean_label().chars().count())
+            .max()
+            .unwrap_or(0);
+        let shortcut_width = menu
+            .items
+            .iter()
+            .filter_map(|item| match &item.submenu {
+                Some(_) => Some(1), // submenu marker
+                None => item.shortcut_text().map(|text| text.chars().count()),
+            })
+            .max()
+            .unwrap_or(0);
+
+        // Frame + one space of padding on each side of the label
+        let mut width = label_width + 4;
+        if shortcut_width > 0 {
+            width += shortcut_width + 2;
+        }
+        (width as u16, menu.items.len() as u16 + 2)
+    }
+
+    /// Moves the menu up and/or left so that it and its shadow stay on a
+    /// screen of the given size
+    pub fn fit_to_screen(&mut self, screen_width: u16, screen_height: u16) {
+        self.screen_size = (screen_width, screen_height);
+        self.bounds = Self::fit(self.bounds, screen_width, screen_height);
+    }
+
+    fn fit(bounds: TRect, screen_width: u16, screen_height: u16) -> TRect {
+        let mut fitted = bounds;
+        // Leave room for the shadow: two columns right, one row below
+        if fitted.x + fitted.width + 2 > screen_width {
+            fitted.x = screen_width.saturating_sub(fitted.width + 2);
         }
+        if fitted.y + fitted.height + 1 > screen_height {
+            fitted.y = screen_height.saturating_sub(fitted.height + 1);
+        }
+        fitted
     }
 
     fn move_up(&mut self) {
@@ -47,49 +122,218 @@ impl TDropDownMenu {
     fn current_item(&self) -> Option<&TMenuItem> {
         self.menu.items.get(self.selected_index)
     }
-}
-
-impl TView for TDropDownMenu {
-    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
-        let x0 = offset.0 + self.bounds.x;
-        let y0 = offset.1 + self.bounds.y;
 
-        for (i, item) in self.menu.items.iter().enumerate() {
-            let y = y0 + i as u16;
-            let is_selected = i == self.selected_index;
+    /// Returns true if `pt` lies on this menu or any open submenu
+    pub fn contains(&self, pt: TPoint) -> bool {
+        self.bounds.contains(pt) || self.submenu.as_ref().is_some_and(|sub| sub.contains(pt))
+    }
 
-            let label = item.clean_label();
-            let display = if is_selected {
-                format!("> {} ", label)
-            } else {
-                format!("  {} ", label)
-            };
+    /// Opens the submenu of the selected item, or posts its command
+    fn activate(&mut self, queue: &TEventQueue) {
+        let Some(item) = self.current_item().filter(|item| !item.disabled) else {
+            return;
+        };
+        match &item.submenu {
+            Some(menu) => {
+                let at = TRect::new(
+                    self.bounds.x + self.bounds.width.saturating_sub(2),
+                    self.bounds.y + 1 + self.selected_index as u16,
+                    0,
+                    0,
+                );
+                let mut submenu = TDropDownMenu::new(menu.clone(), at);
+                submenu.fit_to_screen(self.screen_size.0, self.screen_size.1);
+                submenu.set_focus(true);
+                self.submenu = Some(Box::new(submenu));
+            }
+            None => queue.put_event(TEvent::Command(item.command)),
+        }
+    }
 
-            buffer.write_str(x0, y, &display);
+    fn select(&mut self, index: usize) -> bool {
+        match self.menu.items.get(index) {
+            Some(item) if !item.disabled => {
+                if index != self.selected_index {
+                    self.submenu = None;
+                }
+                self.selected_index = index;
+                true
+            }
+            _ => false,
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
-        if let TEvent::Key(KeyEvent { code, .. }) = event {
-            match code {
-                KeyCode::Up => self.move_up(),
-                KeyCode::Down => self.move_down(),
-                KeyCode::Enter => {
-                    if let Some(item) = self.current_item() {
-                        if !item.disabled {
-                            queue.put_event(TEvent::Command(item.command));
+    fn process(&mut self, event: TEvent, queue: &TEventQueue) -> MenuAction {
+        match event {
+            TEvent::Key(key) => {
+                if let Some(submenu) = self.submenu.as_mut() {
+                    match submenu.process(TEvent::Key(key), queue) {
+                        MenuAction::Close => {
+                            self.submenu = None;
+                            return MenuAction::Handled;
+                        }
+                        MenuAction::Handled => return MenuAction::Handled,
+                        MenuAction::Unhandled => return self.process_shortcut(key.code, queue),
+                    }
+                }
+                self.process_key(key, queue)
+            }
+            TEvent::Mouse(mouse) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                if let Some(submenu) = self.submenu.as_mut() {
+                    if submenu.contains(pt) {
+                        if submenu.process(event, queue) == MenuAction::Close {
+                            self.submenu = None;
+                        }
+                        return MenuAction::Handled;
+                    }
+                }
+
+                if !self.bounds.contains(pt) {
+                    return match mouse.kind {
+                        MouseEventKind::Down(_) => MenuAction::Close,
+                        _ => MenuAction::Unhandled,
+                    };
+                }
+
+                let row = pt.y - self.bounds.y;
+                if row == 0 || row as usize > self.menu.items.len() {
+                    return MenuAction::Handled;
+                }
+                let index = row as usize - 1;
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Up(MouseButton::Left) => {
+                        // Submenus open on press, commands fire on release
+                        let pressed = matches!(mouse.kind, MouseEventKind::Down(_));
+                        let opens_submenu = self.menu.items[index].submenu.is_some();
+                        if self.select(index) && self.submenu.is_none() && opens_submenu == pressed {
+                            self.activate(queue);
                         }
                     }
+                    MouseEventKind::Moved | MouseEventKind::Drag(_) => {
+                        self.select(index);
+                    }
+                    _ => {}
+                }
+                MenuAction::Handled
+            }
+            _ => MenuAction::Unhandled,
+        }
+    }
+
+    fn process_key(&mut self, key: KeyEvent, queue: &TEventQueue) -> MenuAction {
+        match key.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Home => {
+                self.selected_index = 0;
+                if self.menu.items.first().is_some_and(|item| item.disabled) {
+                    self.move_down();
                 }
-                KeyCode::Esc => {
-                    queue.put_event(TEvent::Command(0)); // 0 = close menu
+            }
+            KeyCode::End => {
+                self.selected_index = self.menu.items.len().saturating_sub(1);
+                if self.menu.items.last().is_some_and(|item| item.disabled) {
+                    self.move_up();
                 }
-                KeyCode::Right => {
-                    // Submenu logic can go here
+            }
+            KeyCode::Enter => self.activate(queue),
+            KeyCode::Right if self.current_item().is_some_and(|item| item.submenu.is_some()) => {
+                self.activate(queue);
+            }
+            KeyCode::Esc | KeyCode::Left => return MenuAction::Close,
+            code => {
+                let hotkey = match code {
+                    KeyCode::Char(c) => self
+                        .menu
+                        .items
+                        .iter()
+                        .position(|item| !item.disabled && item.hotkey() == Some(c.to_ascii_lowercase())),
+                    _ => None,
+                };
+                match hotkey {
+                    Some(index) => {
+                        self.select(index);
+                        self.activate(queue);
+                    }
+                    None => return self.process_shortcut(code, queue),
                 }
-                _ => {}
             }
         }
+        MenuAction::Handled
+    }
+
+    fn process_shortcut(&mut self, code: KeyCode, queue: &TEventQueue) -> MenuAction {
+        match self.menu.find_shortcut(code) {
+            Some(item) => {
+                queue.put_event(TEvent::Command(item.command));
+                MenuAction::Handled
+            }
+            None => MenuAction::Unhandled,
+        }
+    }
+
+    fn draw_item(&self, buffer: &mut ScreenBuffer, item: &TMenuItem, x: u16, y: u16, selected: bool) {
+        let inner_width = self.bounds.width.saturating_sub(2);
+        let bg = if selected { SELECTED_BG } else { MENU_BG };
+        let fg = if item.disabled { DISABLED_FG } else { MENU_FG };
+
+        buffer.fill(TRect::new(x, y, inner_width, 1), ' ', fg, bg);
+
+        // Label, with the ~hotkey~ segment highlighted
+        let mut cx = x + 1;
+        let mut in_hotkey = false;
+        for ch in item.label.chars() {
+            if ch == '~' {
+                in_hotkey = !in_hotkey;
+                continue;
+            }
+            let ch_fg = if in_hotkey && !item.disabled { HOTKEY_FG } else { fg };
+            buffer.set_styled(cx, y, ch, ch_fg, bg);
+            cx += 1;
+        }
+
+        // Shortcut text or submenu marker, right-aligned
+        let shortcut = match &item.submenu {
+            Some(_) => Some("►".to_string()),
+            None => item.shortcut_text(),
+        };
+        if let Some(shortcut) = shortcut {
+            let sx = (x + inner_width).saturating_sub(shortcut.chars().count() as u16 + 1);
+            buffer.write_styled(sx, y, &shortcut, fg, bg);
+        }
+    }
+}
+
+impl TView for TDropDownMenu {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let origin = TRect::new(
+            offset.0 + self.bounds.x,
+            offset.1 + self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        let frame = Self::fit(origin, buffer.width(), buffer.height());
+
+        buffer.fill(frame, ' ', MENU_FG, MENU_BG);
+        buffer.draw_frame(frame, MENU_FG, MENU_BG);
+
+        for (i, item) in self.menu.items.iter().enumerate() {
+            let y = frame.y + 1 + i as u16;
+            self.draw_item(buffer, item, frame.x + 1, y, i == self.selected_index);
+        }
+
+        buffer.shadow(frame);
+
+        if let Some(submenu) = &self.submenu {
+            submenu.draw(buffer, offset);
+        }
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        if self.process(event, queue) == MenuAction::Close {
+            queue.put_event(TEvent::Command(CM_CANCEL));
+        }
     }
 
     fn get_bounds(&self) -> TRect {
@@ -107,4 +351,73 @@ impl TView for TDropDownMenu {
     fn is_focusable(&self) -> bool {
         true
     }
+
+    /// A popup menu closes with the command of the item chosen
+    fn ends_modal(&self, _command: u16) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        match &self.submenu {
+            Some(submenu) => submenu.help_context(),
+            None => self.current_item().and_then(|item| item.help_context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "Save as..." is the longest label and "F12" the longest shortcut
+    fn file_menu() -> TMenu {
+        TMenu::new(vec![
+            TMenuItem::new("~O~pen", 1).with_keycode(KeyCode::F(3)),
+            TMenuItem::new("~S~ave as...", 2).with_keycode(KeyCode::F(12)),
+            TMenuItem::new("E~x~it", 3),
+        ])
+    }
+
+    fn text(buffer: &ScreenBuffer, x: u16, y: u16, width: u16) -> String {
+        (x..x + width).filter_map(|x| buffer.get(x, y)).map(|cell| cell.ch).collect()
+    }
+
+    #[test]
+    fn frame_fits_longest_label_and_shortcut() {
+        // Label plus a space either side, shortcut plus two spaces, frame
+        assert_eq!(TDropDownMenu::menu_size(&file_menu()), (10 + 2 + 3 + 2 + 2, 5));
+
+        let menu = TDropDownMenu::new(file_menu(), TRect::new(2, 1, 0, 0));
+        let mut buffer = ScreenBuffer::new(40, 12);
+        menu.draw(&mut buffer, (0, 0));
+        assert_eq!(text(&buffer, 2, 1, 19), "┌─────────────────┐");
+        assert_eq!(text(&buffer, 2, 2, 19), "│ Open         F3 │");
+        assert_eq!(text(&buffer, 2, 3, 19), "│ Save as...  F12 │");
+        assert_eq!(text(&buffer, 2, 4, 19), "│ Exit            │");
+        assert_eq!(text(&buffer, 2, 5, 19), "└─────────────────┘");
+
+        // A submenu marker counts as a one-char shortcut
+        let nested = TMenu::new(vec![TMenuItem::new("~R~ecent", 4).with_submenu(file_menu())]);
+        assert_eq!(TDropDownMenu::menu_size(&nested), (6 + 2 + 1 + 2 + 2, 3));
+    }
+
+    #[test]
+    fn menu_near_edge_moves_inside_screen() {
+        let mut menu = TDropDownMenu::new(file_menu(), TRect::new(35, 9, 0, 0));
+        menu.fit_to_screen(40, 12);
+        // Room is left for the shadow, two columns right and one row below
+        assert_eq!(menu.bounds, TRect::new(19, 6, 19, 5));
+
+        let mut buffer = ScreenBuffer::new(40, 12);
+        menu.draw(&mut buffer, (0, 0));
+        assert_eq!(text(&buffer, 19, 6, 19), "┌─────────────────┐");
+        assert_eq!(text(&buffer, 19, 10, 19), "└─────────────────┘");
+        assert_eq!(buffer.get(39, 11).unwrap().bg, Color::Black);
+
+        // Drawing fits the menu to the buffer even without fit_to_screen
+        let menu = TDropDownMenu::new(file_menu(), TRect::new(35, 9, 0, 0));
+        let mut buffer = ScreenBuffer::new(40, 12);
+        menu.draw(&mut buffer, (0, 0));
+        assert_eq!(text(&buffer, 19, 6, 19), "┌─────────────────┐");
+    }
 }
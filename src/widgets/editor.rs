@@ -0,0 +1,1023 @@
+use crate::core::clipboard::TClipboard;
+use crate::core::command::CM_OK;
+use crate::core::gapbuffer::GapBuffer;
+use crate::core::rect::TRect;
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::input::WHEEL_STEP;
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::scrollbar::{Orientation, TScrollBar};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+use std::cell::Cell;
+
+const EDITOR_FG: Color = Color::Yellow;
+const EDITOR_BG: Color = Color::Blue;
+const SELECTED_FG: Color = Color::Black;
+const SELECTED_BG: Color = Color::Cyan;
+const CURSOR_FG: Color = Color::Blue;
+const CURSOR_BG: Color = Color::White;
+const OVERWRITE_CURSOR_BG: Color = Color::Yellow;
+const SCROLLBAR_FG: Color = Color::Cyan;
+const INDICATOR_FG: Color = Color::White;
+
+/// Columns taken by the line:column indicator in the bottom row
+const INDICATOR_WIDTH: u16 = 12;
+
+/// How `TEditor::find` compares text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TFindOptions {
+    pub match_case: bool,
+    pub whole_words: bool,
+}
+
+/// Step of the Ctrl+F or Ctrl+H prompts waiting for its `TEvent::Reply`
+#[derive(Debug, Clone)]
+enum SearchPrompt {
+    Find,
+    ReplaceText,
+    ReplaceWith(String),
+}
+
+/// One undoable change: `removed` was replaced by `inserted` at `pos`
+#[derive(Debug, Clone)]
+struct EditAction {
+    pos: usize,
+    removed: String,
+    inserted: String,
+    cursor_before: usize,
+    anchor_before: Option<usize>,
+}
+
+/// Multi-line text editor. The text lives in a gap buffer; positions count
+/// chars and lines end with `\n`. The right column holds a vertical scroll
+/// bar and the bottom row a line:column indicator and a horizontal scroll
+/// bar. Ctrl+F and Ctrl+H ask for the text to find or replace through
+/// `TEvent::InputBox`.
+pub struct TEditor {
+    pub bounds: TRect,
+    pub focused: bool,
+    pub help_context: Option<u16>,
+    pub modified: bool,             // Text changed since `set_text` or `set_modified(false)`
+    pub auto_indent: bool,          // Enter copies the current line's indentation
+    pub expand_tabs: bool,          // Tab inserts spaces up to the next tab stop
+    pub tab_size: usize,
+    pub overwrite: bool,            // Typing replaces the char under the cursor
+    pub find_options: TFindOptions, // Options of the Ctrl+F and Ctrl+H prompts
+    buffer: GapBuffer,
+    line_starts: Vec<usize>,        // Position of the first char of each line
+    cursor: usize,
+    sel_anchor: Option<usize>,      // Other end of the selection
+    preferred_col: Option<usize>,   // Screen column kept while moving up and down
+    scroll_x: usize,                // First visible screen column
+    scroll_y: usize,                // First visible line
+    undo_stack: Vec<EditAction>,
+    redo_stack: Vec<EditAction>,
+    saved_depth: Option<usize>,     // Undo stack depth of the unmodified text, if undo or redo can reach it
+    last_find: Option<(String, TFindOptions)>,
+    search_prompt: Option<SearchPrompt>,
+    longest_line: Cell<Option<(usize, usize)>>, // Tab size and width of the longest line, once measured
+    clipboard: TClipboard,
+    colors: (Color, Color),
+    v_scroll_bar: TScrollBar,
+    h_scroll_bar: TScrollBar,
+    mouse_down: bool,
+}
+
+impl TEditor {
+    pub fn new(bounds: TRect) -> Self {
+        let mut editor = Self {
+            bounds,
+            focused: true,
+            help_context: None,
+            modified: false,
+            auto_indent: true,
+            expand_tabs: true,
+            tab_size: 8,
+            overwrite: false,
+            find_options: TFindOptions::default(),
+            buffer: GapBuffer::new(),
+            line_starts: vec![0],
+            cursor: 0,
+            sel_anchor: None,
+            preferred_col: None,
+            scroll_x: 0,
+            scroll_y: 0,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_depth: Some(0),
+            last_find: None,
+            search_prompt: None,
+            longest_line: Cell::new(None),
+            clipboard: TClipboard::shared(),
+            colors: (EDITOR_FG, EDITOR_BG),
+            v_scroll_bar: TScrollBar::new(TRect::new(0, 0, 0, 0), Orientation::Vertical),
+            h_scroll_bar: TScrollBar::new(TRect::new(0, 0, 0, 0), Orientation::Horizontal),
+            mouse_down: false,
+        };
+        editor.set_colors(EDITOR_FG, EDITOR_BG);
+        editor.layout_scroll_bars();
+        editor
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    /// Uses `clipboard` for cut, copy and paste instead of the application one
+    pub fn set_clipboard(&mut self, clipboard: TClipboard) {
+        self.clipboard = clipboard;
+    }
+
+    /// Sets the text colors; selection and scroll bars keep their
+    /// foreground colors
+    pub fn set_colors(&mut self, fg: Color, bg: Color) {
+        self.colors = (fg, bg);
+        self.v_scroll_bar.set_colors(SCROLLBAR_FG, bg);
+        self.h_scroll_bar.set_colors(SCROLLBAR_FG, bg);
+    }
+
+    /// Marks the text as changed or, with `false`, as matching the saved
+    /// file; undoing back to this point clears the flag again
+    pub fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+        self.saved_depth = (!modified).then_some(self.undo_stack.len());
+    }
+
+    pub fn get_text(&self) -> String {
+        self.buffer.text()
+    }
+
+    /// Replaces the whole text, clearing the undo history and modified flag
+    pub fn set_text(&mut self, text: &str) {
+        self.buffer = GapBuffer::from(text.replace("\r\n", "\n").as_str());
+        self.rebuild_lines();
+        self.longest_line.set(None);
+        self.cursor = 0;
+        self.sel_anchor = None;
+        self.preferred_col = None;
+        self.scroll_x = 0;
+        self.scroll_y = 0;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.set_modified(false);
+        self.sync_scroll_bars();
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the text of `line` without its line break
+    pub fn line_text(&self, line: usize) -> String {
+        self.buffer.slice(self.line_start(line)..self.line_end(line))
+    }
+
+    /// Zero-based line and screen column of the cursor
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let line = self.line_of(self.cursor);
+        (line, self.screen_col(line, self.cursor))
+    }
+
+    /// Moves the cursor to zero-based `line` and char column `col`
+    pub fn set_cursor(&mut self, line: usize, col: usize) {
+        let line = line.min(self.line_count() - 1);
+        let pos = (self.line_start(line) + col).min(self.line_end(line));
+        self.move_to(pos, false);
+        self.scroll_to_cursor();
+    }
+
+    /// Returns the selected char range, if any text is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.sel_anchor?;
+        match anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some((anchor, self.cursor)),
+            std::cmp::Ordering::Greater => Some((self.cursor, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|(start, end)| self.buffer.slice(start..end))
+    }
+
+    pub fn select_all(&mut self) {
+        self.sel_anchor = Some(0);
+        self.cursor = self.buffer.len();
+        self.scroll_to_cursor();
+    }
+
+    /// Inserts `text` at the cursor, replacing the selection
+    pub fn insert_text(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n");
+        let (start, end) = self.selection().unwrap_or((self.cursor, self.cursor));
+        self.edit(start, end - start, &text, false);
+        self.scroll_to_cursor();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self) {
+        let Some(action) = self.undo_stack.pop() else {
+            return;
+        };
+        self.replace_raw(action.pos, action.inserted.chars().count(), &action.removed);
+        self.cursor = action.cursor_before;
+        self.sel_anchor = action.anchor_before;
+        self.preferred_col = None;
+        self.redo_stack.push(action);
+        self.modified = self.saved_depth != Some(self.undo_stack.len());
+        self.scroll_to_cursor();
+    }
+
+    pub fn redo(&mut self) {
+        let Some(action) = self.redo_stack.pop() else {
+            return;
+        };
+        self.replace_raw(action.pos, action.removed.chars().count(), &action.inserted);
+        self.cursor = action.pos + action.inserted.chars().count();
+        self.sel_anchor = None;
+        self.preferred_col = None;
+        self.undo_stack.push(action);
+        self.modified = self.saved_depth != Some(self.undo_stack.len());
+        self.scroll_to_cursor();
+    }
+
+    pub fn copy(&self) {
+        if let Some(text) = self.selected_text() {
+            self.clipboard.set_text(&text);
+        }
+    }
+
+    pub fn cut(&mut self) {
+        if let Some((start, end)) = self.selection() {
+            self.copy();
+            self.edit(start, end - start, "", false);
+            self.scroll_to_cursor();
+        }
+    }
+
+    pub fn paste(&mut self) {
+        let text = self.clipboard.text();
+        if !text.is_empty() {
+            self.insert_text(&text);
+        }
+    }
+
+    /// Selects the next match of `text` after the cursor. Returns false if
+    /// there is none. The search is remembered for `find_next` (F3, Ctrl+L).
+    pub fn find(&mut self, text: &str, options: TFindOptions) -> bool {
+        self.last_find = Some((text.to_string(), options));
+        self.find_next()
+    }
+
+    /// Repeats the last search
+    pub fn find_next(&mut self) -> bool {
+        let Some((text, options)) = self.last_find.clone() else {
+            return false;
+        };
+        let from = self.selection().map_or(self.cursor, |(_, end)| end);
+        match self.search(&text, options, from) {
+            Some(start) => {
+                self.sel_anchor = Some(start);
+                self.cursor = start + text.chars().count();
+                self.preferred_col = None;
+                self.scroll_to_cursor();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the selected match of `text` and selects the next one, or
+    /// with `all` replaces every match in the text. Returns the number of
+    /// replacements made.
+    pub fn replace(&mut self, text: &str, replacement: &str, options: TFindOptions, all: bool) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        let len = text.chars().count();
+        let mut count = 0;
+        if all {
+            let mut from = 0;
+            while let Some(start) = self.search(text, options, from) {
+                self.edit(start, len, replacement, false);
+                from = start + replacement.chars().count();
+                count += 1;
+            }
+        } else {
+            if let Some((start, end)) = self.selection() {
+                if self.matches_at(text, options, start) && end - start == len {
+                    self.edit(start, len, replacement, false);
+                    count = 1;
+                }
+            }
+            self.find(text, options);
+        }
+        self.last_find = Some((text.to_string(), options));
+        self.scroll_to_cursor();
+        count
+    }
+
+    /// Returns the position of the first match of `text` at or after `from`
+    fn search(&self, text: &str, options: TFindOptions, from: usize) -> Option<usize> {
+        let len = text.chars().count();
+        if len == 0 {
+            return None;
+        }
+        (from..=self.buffer.len().saturating_sub(len)).find(|&pos| self.matches_at(text, options, pos))
+    }
+
+    fn matches_at(&self, text: &str, options: TFindOptions, pos: usize) -> bool {
+        let len = text.chars().count();
+        let same = |a: char, b: char| {
+            if options.match_case {
+                a == b
+            } else {
+                a.to_lowercase().eq(b.to_lowercase())
+            }
+        };
+        if pos + len > self.buffer.len() || self.buffer.chars_from(pos).zip(text.chars()).any(|(a, b)| !same(a, b)) {
+            return false;
+        }
+        if options.whole_words {
+            let before = pos.checked_sub(1).and_then(|p| self.buffer.char_at(p));
+            let after = self.buffer.char_at(pos + len);
+            if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn line_start(&self, line: usize) -> usize {
+        self.line_starts[line]
+    }
+
+    /// Position of the line break ending `line`, or the text end
+    fn line_end(&self, line: usize) -> usize {
+        self.line_starts.get(line + 1).map_or(self.buffer.len(), |next| next - 1)
+    }
+
+    fn line_of(&self, pos: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= pos) - 1
+    }
+
+    fn rebuild_lines(&mut self) {
+        self.line_starts = vec![0];
+        for (i, ch) in self.buffer.chars_from(0).enumerate() {
+            if ch == '\n' {
+                self.line_starts.push(i + 1);
+            }
+        }
+    }
+
+    /// Screen column of `pos` on `line`, with tabs expanded
+    fn screen_col(&self, line: usize, pos: usize) -> usize {
+        let start = self.line_start(line);
+        self.buffer.chars_from(start).take(pos - start).fold(0, |col, ch| col + self.char_width(ch, col))
+    }
+
+    /// Position on `line` shown at screen column `col`
+    fn pos_at_col(&self, line: usize, col: usize) -> usize {
+        let (start, end) = (self.line_start(line), self.line_end(line));
+        let mut x = 0;
+        for (i, ch) in self.buffer.chars_from(start).take(end - start).enumerate() {
+            let width = self.char_width(ch, x);
+            if x + width > col {
+                return start + i;
+            }
+            x += width;
+        }
+        end
+    }
+
+    fn char_width(&self, ch: char, col: usize) -> usize {
+        if ch == '\t' {
+            self.tab_size.max(1) - col % self.tab_size.max(1)
+        } else {
+            1
+        }
+    }
+
+    fn text_width(&self) -> usize {
+        self.bounds.width.saturating_sub(1) as usize
+    }
+
+    fn page_height(&self) -> usize {
+        self.bounds.height.saturating_sub(1) as usize
+    }
+
+    /// Screen width of `line`, with tabs expanded
+    fn line_width(&self, line: usize) -> usize {
+        self.screen_col(line, self.line_end(line))
+    }
+
+    /// Width of the longest line. Measuring takes a pass over the whole
+    /// text, so the result is kept until an edit may have shortened it.
+    fn longest_line(&self) -> usize {
+        match self.longest_line.get() {
+            Some((tab_size, width)) if tab_size == self.tab_size => width,
+            _ => {
+                let width = (0..self.line_count()).map(|line| self.line_width(line)).max().unwrap_or(0);
+                self.longest_line.set(Some((self.tab_size, width)));
+                width
+            }
+        }
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let (line, col) = self.cursor_position();
+        let (width, height) = (self.text_width().max(1), self.page_height().max(1));
+        if line < self.scroll_y {
+            self.scroll_y = line;
+        } else if line >= self.scroll_y + height {
+            self.scroll_y = line + 1 - height;
+        }
+        if col < self.scroll_x {
+            self.scroll_x = col;
+        } else if col >= self.scroll_x + width {
+            self.scroll_x = col + 1 - width;
+        }
+        self.sync_scroll_bars();
+    }
+
+    /// Puts the vertical bar in the right column and the horizontal bar
+    /// after the indicator in the bottom row, if there is room for it
+    fn layout_scroll_bars(&mut self) {
+        let (x, y) = (self.bounds.x, self.bounds.y);
+        let (width, height) = (self.text_width() as u16, self.page_height() as u16);
+        self.v_scroll_bar.set_bounds(TRect::new(x + width, y, 1, height));
+        let bar_width = if width > INDICATOR_WIDTH + 2 { width - INDICATOR_WIDTH } else { 0 };
+        self.h_scroll_bar.set_bounds(TRect::new(x + INDICATOR_WIDTH, y + height, bar_width, 1));
+    }
+
+    /// Moves the scroll bars to the current scroll position
+    fn sync_scroll_bars(&mut self) {
+        self.v_scroll_bar.set_params(self.scroll_y, self.max_scroll_y(), self.page_height());
+        // Without room for the horizontal bar the longest line need not be measured
+        if self.h_scroll_bar.get_bounds().width > 0 {
+            self.h_scroll_bar.set_params(self.scroll_x, self.max_scroll_x(), self.text_width());
+        }
+    }
+
+    fn max_scroll_y(&self) -> usize {
+        self.line_count().saturating_sub(self.page_height())
+    }
+
+    fn max_scroll_x(&self) -> usize {
+        (self.longest_line() + 1).saturating_sub(self.text_width())
+    }
+
+    /// Replaces `len` chars at `pos` with `text` without recording undo
+    fn replace_raw(&mut self, pos: usize, len: usize, text: &str) {
+        // An edit within one line only needs that line measured, unless it
+        // shortened the longest line
+        let one_line = !text.contains('\n') && !self.buffer.chars_from(pos).take(len).any(|ch| ch == '\n');
+        let old_width = one_line.then(|| self.line_width(self.line_of(pos)));
+
+        self.buffer.delete(pos..pos + len);
+        // Drop the starts of lines whose breaks were removed, then shift the rest
+        self.line_starts.retain(|&start| start <= pos || start > pos + len);
+        let inserted = text.chars().count();
+        for start in self.line_starts.iter_mut().filter(|start| **start > pos) {
+            *start = *start - len + inserted;
+        }
+        self.buffer.insert(pos, text);
+        let line = self.line_of(pos);
+        let new_starts = text.chars().enumerate().filter(|&(_, ch)| ch == '\n').map(|(i, _)| pos + i + 1);
+        self.line_starts.splice(line + 1..line + 1, new_starts);
+
+        let longest = match (self.longest_line.get(), old_width) {
+            (Some((tab_size, longest)), Some(old_width)) if tab_size == self.tab_size => {
+                let width = self.line_width(line);
+                if width >= longest {
+                    Some((tab_size, width))
+                } else if old_width < longest {
+                    Some((tab_size, longest))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        self.longest_line.set(longest);
+    }
+
+    /// Replaces `len` chars at `pos` with `text`, recording the change for
+    /// undo. Typing merges into the previous change when `merge` is set.
+    fn edit(&mut self, pos: usize, len: usize, text: &str, merge: bool) {
+        let removed = self.buffer.slice(pos..pos + len);
+        let inserted_len = text.chars().count();
+        // The saved state is lost once it only lives on the redo stack, and
+        // edits don't merge into the change that reached it
+        if self.saved_depth.is_some_and(|depth| depth > self.undo_stack.len()) {
+            self.saved_depth = None;
+        }
+        let mergeable = merge
+            && self.saved_depth != Some(self.undo_stack.len())
+            && len == 0
+            && !text.contains('\n')
+            && self.undo_stack.last().is_some_and(|last| {
+                last.removed.is_empty()
+                    && !last.inserted.ends_with(char::is_whitespace)
+                    && last.pos + last.inserted.chars().count() == pos
+            });
+        if mergeable {
+            if let Some(last) = self.undo_stack.last_mut() {
+                last.inserted.push_str(text);
+            }
+        } else {
+            self.undo_stack.push(EditAction {
+                pos,
+                removed,
+                inserted: text.to_string(),
+                cursor_before: self.cursor,
+                anchor_before: self.sel_anchor,
+            });
+        }
+        self.redo_stack.clear();
+        self.replace_raw(pos, len, text);
+        self.cursor = pos + inserted_len;
+        self.sel_anchor = None;
+        self.preferred_col = None;
+        self.modified = true;
+    }
+
+    /// Moves the cursor, extending the selection when `extend` is set
+    fn move_to(&mut self, pos: usize, extend: bool) {
+        if extend {
+            self.sel_anchor.get_or_insert(self.cursor);
+        } else {
+            self.sel_anchor = None;
+        }
+        self.cursor = pos.min(self.buffer.len());
+    }
+
+    /// Moves up or down by `lines`, keeping the screen column
+    fn move_lines(&mut self, lines: isize, extend: bool) {
+        let (line, col) = self.cursor_position();
+        let col = *self.preferred_col.get_or_insert(col);
+        let target = line.saturating_add_signed(lines).min(self.line_count() - 1);
+        let pos = self.pos_at_col(target, col);
+        self.move_to(pos, extend);
+        self.preferred_col = Some(col);
+    }
+
+    fn prev_word(&self, pos: usize) -> usize {
+        let mut pos = pos;
+        while pos > 0 && !self.buffer.char_at(pos - 1).is_some_and(is_word_char) {
+            pos -= 1;
+        }
+        while pos > 0 && self.buffer.char_at(pos - 1).is_some_and(is_word_char) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn next_word(&self, pos: usize) -> usize {
+        let mut pos = pos;
+        while self.buffer.char_at(pos).is_some_and(is_word_char) {
+            pos += 1;
+        }
+        while self.buffer.char_at(pos).is_some_and(|ch| !is_word_char(ch)) {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn type_char(&mut self, ch: char) {
+        match self.selection() {
+            Some((start, end)) => self.edit(start, end - start, ch.encode_utf8(&mut [0; 4]), false),
+            None => {
+                let at_line_end = matches!(self.buffer.char_at(self.cursor), None | Some('\n'));
+                let len = if self.overwrite && !at_line_end { 1 } else { 0 };
+                self.edit(self.cursor, len, ch.encode_utf8(&mut [0; 4]), len == 0);
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        let mut text = String::from("\n");
+        if self.auto_indent {
+            let line = self.line_of(self.cursor);
+            let indent = self
+                .buffer
+                .chars_from(self.line_start(line))
+                .take(self.cursor - self.line_start(line))
+                .take_while(|&ch| ch == ' ' || ch == '\t');
+            text.extend(indent);
+        }
+        self.insert_text(&text);
+    }
+
+    fn tab(&mut self) {
+        if self.expand_tabs {
+            let (_, col) = self.cursor_position();
+            let spaces = self.char_width('\t', col);
+            self.insert_text(&" ".repeat(spaces));
+        } else {
+            self.insert_text("\t");
+        }
+    }
+
+    /// Deletes the selection or the chars from `from` to `to`
+    fn delete_to(&mut self, from: usize, to: usize) {
+        let (start, end) = self.selection().unwrap_or((from.min(to), from.max(to)));
+        if end > start {
+            self.edit(start, end - start, "", false);
+        }
+    }
+
+    /// Asks for the text of a find or replace step through `TEvent::InputBox`
+    fn prompt(&mut self, prompt: SearchPrompt, queue: &TEventQueue) {
+        let last = self.last_find.as_ref().map_or(String::new(), |(text, _)| text.clone());
+        let selected = self.selected_text().filter(|text| !text.contains('\n'));
+        let (title, label, value) = match &prompt {
+            SearchPrompt::Find => ("Find", "Text to find:", selected.unwrap_or(last)),
+            SearchPrompt::ReplaceText => ("Replace", "Text to find:", selected.unwrap_or(last)),
+            SearchPrompt::ReplaceWith(_) => ("Replace", "New text:", String::new()),
+        };
+        self.search_prompt = Some(prompt);
+        queue.put_event(TEvent::InputBox { title: title.to_string(), label: label.to_string(), value });
+    }
+
+    /// Finds or replaces once a prompt is answered. Replacing replaces
+    /// every match.
+    fn handle_reply(&mut self, command: u16, text: String, queue: &TEventQueue) {
+        let Some(prompt) = self.search_prompt.take() else {
+            return;
+        };
+        if command != CM_OK {
+            return;
+        }
+        let options = self.find_options;
+        let found = match prompt {
+            SearchPrompt::Find | SearchPrompt::ReplaceText if text.is_empty() => return,
+            SearchPrompt::Find => self.find(&text, options),
+            SearchPrompt::ReplaceText => {
+                self.prompt(SearchPrompt::ReplaceWith(text), queue);
+                return;
+            }
+            SearchPrompt::ReplaceWith(find) => self.replace(&find, &text, options, true) > 0,
+        };
+        if !found {
+            queue.put_event(TEvent::Error("Search string not found".to_string()));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, queue: &TEventQueue) {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let page = self.page_height().max(1) as isize;
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.select_all(),
+            KeyCode::Char('c') if ctrl => self.copy(),
+            KeyCode::Char('x') if ctrl => self.cut(),
+            KeyCode::Char('v') if ctrl => self.paste(),
+            KeyCode::Char('z') | KeyCode::Char('Z') if ctrl && shift => self.redo(),
+            KeyCode::Char('z') if ctrl => self.undo(),
+            KeyCode::Char('y') if ctrl => self.redo(),
+            KeyCode::Char('f') if ctrl => self.prompt(SearchPrompt::Find, queue),
+            KeyCode::Char('h') if ctrl => self.prompt(SearchPrompt::ReplaceText, queue),
+            KeyCode::Char('l') if ctrl => {
+                self.find_next();
+            }
+            KeyCode::F(3) => {
+                self.find_next();
+            }
+            KeyCode::Char(c) if !ctrl && !alt => self.type_char(c),
+            KeyCode::Enter => self.new_line(),
+            KeyCode::Tab => self.tab(),
+            KeyCode::Backspace if ctrl || alt => self.delete_to(self.prev_word(self.cursor), self.cursor),
+            KeyCode::Backspace => self.delete_to(self.cursor.saturating_sub(1), self.cursor),
+            KeyCode::Delete if shift => self.cut(),
+            KeyCode::Delete if ctrl => self.delete_to(self.cursor, self.next_word(self.cursor)),
+            KeyCode::Delete => self.delete_to(self.cursor, (self.cursor + 1).min(self.buffer.len())),
+            KeyCode::Insert if ctrl => self.copy(),
+            KeyCode::Insert if shift => self.paste(),
+            KeyCode::Insert => self.overwrite = !self.overwrite,
+            KeyCode::Left if ctrl => self.move_to(self.prev_word(self.cursor), shift),
+            KeyCode::Right if ctrl => self.move_to(self.next_word(self.cursor), shift),
+            KeyCode::Left => self.move_to(self.cursor.saturating_sub(1), shift),
+            KeyCode::Right => self.move_to(self.cursor + 1, shift),
+            KeyCode::Up => self.move_lines(-1, shift),
+            KeyCode::Down => self.move_lines(1, shift),
+            KeyCode::PageUp => {
+                self.scroll_y = self.scroll_y.saturating_sub(page as usize);
+                self.move_lines(-page, shift);
+            }
+            KeyCode::PageDown => {
+                self.scroll_y = (self.scroll_y + page as usize).min(self.max_scroll_y());
+                self.move_lines(page, shift);
+            }
+            KeyCode::Home if ctrl => self.move_to(0, shift),
+            KeyCode::End if ctrl => self.move_to(self.buffer.len(), shift),
+            KeyCode::Home => self.move_to(self.line_start(self.line_of(self.cursor)), shift),
+            KeyCode::End => self.move_to(self.line_end(self.line_of(self.cursor)), shift),
+            _ => return,
+        }
+        if !matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown) {
+            self.preferred_col = None;
+        }
+        self.scroll_to_cursor();
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if let MouseEventKind::Up(_) = mouse.kind {
+            self.mouse_down = false;
+        }
+        if self.v_scroll_bar.handles_mouse(&mouse) {
+            if let Some(value) = self.v_scroll_bar.scroll(&TEvent::Mouse(mouse)) {
+                self.scroll_y = value;
+            }
+            return;
+        }
+        if self.h_scroll_bar.handles_mouse(&mouse) {
+            if let Some(value) = self.h_scroll_bar.scroll(&TEvent::Mouse(mouse)) {
+                self.scroll_x = value;
+            }
+            return;
+        }
+        let (Some(col), Some(row)) = (mouse.column.checked_sub(self.bounds.x), mouse.row.checked_sub(self.bounds.y)) else {
+            return;
+        };
+        let (col, row) = (col as usize, row as usize);
+        let in_text = col < self.text_width() && row < self.page_height();
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if in_text => {
+                let pos = self.pos_at_point(col, row);
+                let shift = mouse.modifiers.contains(KeyModifiers::SHIFT);
+                self.move_to(pos, shift);
+                self.sel_anchor.get_or_insert(pos);
+                self.preferred_col = None;
+                self.mouse_down = true;
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.mouse_down => {
+                let pos = self.pos_at_point(col.min(self.text_width()), row.min(self.page_height()));
+                self.move_to(pos, true);
+                self.scroll_to_cursor();
+            }
+            MouseEventKind::ScrollUp => self.scroll_y = self.scroll_y.saturating_sub(WHEEL_STEP),
+            MouseEventKind::ScrollDown => self.scroll_y = (self.scroll_y + WHEEL_STEP).min(self.max_scroll_y()),
+            _ => {}
+        }
+    }
+
+    /// Text position under local point `col`, `row`
+    fn pos_at_point(&self, col: usize, row: usize) -> usize {
+        let line = (self.scroll_y + row).min(self.line_count() - 1);
+        self.pos_at_col(line, self.scroll_x + col)
+    }
+
+    fn draw_text(&self, buffer: &mut ScreenBuffer, x: u16, y: u16) {
+        let (fg, bg) = self.colors;
+        let selection = self.selection();
+        for row in 0..self.page_height() {
+            let line = self.scroll_y + row;
+            if line >= self.line_count() {
+                break;
+            }
+            let start = self.line_start(line);
+            let mut col = 0;
+            for (i, ch) in self.buffer.chars_from(start).take(self.line_end(line) - start).enumerate() {
+                let width = self.char_width(ch, col);
+                let (fg, bg) = match selection {
+                    Some((sel_start, sel_end)) if start + i >= sel_start && start + i < sel_end => (SELECTED_FG, SELECTED_BG),
+                    _ => (fg, bg),
+                };
+                // Tabs show as spaces and other control chars as dots, so
+                // stray bytes can't reach the terminal
+                let shown = match ch {
+                    '\t' => ' ',
+                    ch if ch.is_control() => '·',
+                    ch => ch,
+                };
+                for c in col..col + width {
+                    if c >= self.scroll_x && c < self.scroll_x + self.text_width() {
+                        buffer.set_styled(x + (c - self.scroll_x) as u16, y + row as u16, shown, fg, bg);
+                    }
+                }
+                col += width;
+                if col >= self.scroll_x + self.text_width() {
+                    break;
+                }
+            }
+        }
+
+        let (line, col) = self.cursor_position();
+        let visible = line >= self.scroll_y
+            && line < self.scroll_y + self.page_height()
+            && col >= self.scroll_x
+            && col < self.scroll_x + self.text_width();
+        if self.focused && visible {
+            let cx = x + (col - self.scroll_x) as u16;
+            let cy = y + (line - self.scroll_y) as u16;
+            let ch = buffer.get(cx, cy).map_or(' ', |cell| cell.ch);
+            let cursor_bg = if self.overwrite { OVERWRITE_CURSOR_BG } else { CURSOR_BG };
+            buffer.set_styled(cx, cy, ch, CURSOR_FG, cursor_bg);
+        }
+    }
+
+    fn draw_scroll_bars(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let (x, y) = (offset.0 + self.bounds.x, offset.1 + self.bounds.y);
+        let (width, height) = (self.text_width(), self.page_height() as u16);
+        self.v_scroll_bar.draw(buffer, offset);
+
+        // Bottom row: line:column indicator, then the horizontal bar
+        let (line, col) = self.cursor_position();
+        let modified = if self.modified { '*' } else { ' ' };
+        let indicator = format!("{}{:>5}:{:<5}", modified, line + 1, col + 1);
+        let indicator: String = indicator.chars().take(width).collect();
+        buffer.write_styled(x, y + height, &indicator, INDICATOR_FG, self.colors.1);
+        self.h_scroll_bar.draw(buffer, offset);
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+impl TView for TEditor {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        let (fg, bg) = self.colors;
+        buffer.fill(TRect::new(x, y, self.bounds.width, self.bounds.height), ' ', fg, bg);
+        if self.bounds.width < 2 || self.bounds.height < 2 {
+            return;
+        }
+        self.draw_text(buffer, x, y);
+        self.draw_scroll_bars(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Key(key) => self.handle_key(key, queue),
+            TEvent::Reply(command, text) => self.handle_reply(command, text, queue),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
+        }
+        self.sync_scroll_bars();
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+        self.layout_scroll_bars();
+        self.scroll_to_cursor();
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.mouse_down = false;
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Enter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> TEditor {
+        let mut editor = TEditor::new(TRect::new(0, 0, 40, 10));
+        editor.set_clipboard(TClipboard::new());
+        editor.set_text(text);
+        editor
+    }
+
+    fn type_text(editor: &mut TEditor, text: &str) {
+        let queue = TEventQueue::new();
+        for ch in text.chars() {
+            editor.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)), &queue);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_restore_text_and_cursor() {
+        let mut editor = editor("one\ntwo");
+        editor.set_cursor(1, 3);
+        type_text(&mut editor, "xyz");
+        editor.insert_text("\nthree");
+        assert_eq!(editor.get_text(), "one\ntwoxyz\nthree");
+
+        editor.undo();
+        assert_eq!(editor.get_text(), "one\ntwoxyz");
+        // Typed chars undo as one change
+        editor.undo();
+        assert_eq!(editor.get_text(), "one\ntwo");
+        assert_eq!(editor.cursor_position(), (1, 3));
+        assert!(!editor.can_undo());
+
+        editor.redo();
+        editor.redo();
+        assert_eq!(editor.get_text(), "one\ntwoxyz\nthree");
+        assert_eq!(editor.line_count(), 3);
+        assert!(!editor.can_redo());
+    }
+
+    #[test]
+    fn undoing_to_the_saved_text_clears_modified() {
+        let mut editor = editor("text");
+        type_text(&mut editor, "ab");
+        assert!(editor.modified);
+        editor.undo();
+        assert!(!editor.modified);
+        editor.redo();
+        assert!(editor.modified);
+
+        // Typing after a save starts a new change
+        editor.set_modified(false);
+        type_text(&mut editor, "c");
+        editor.undo();
+        assert!(!editor.modified);
+        assert_eq!(editor.get_text(), "abtext");
+
+        // Once the saved text is only on the redo stack, an edit loses it
+        editor.undo();
+        type_text(&mut editor, "d");
+        editor.undo();
+        assert!(editor.modified);
+    }
+
+    #[test]
+    fn finds_with_case_and_whole_word_options() {
+        let mut editor = editor("Cat concat\ncat");
+        assert!(editor.find("cat", TFindOptions::default()));
+        assert_eq!(editor.selection(), Some((0, 3)));
+        assert!(editor.find_next());
+        assert_eq!(editor.selection(), Some((7, 10)));
+
+        editor.set_cursor(0, 0);
+        let options = TFindOptions { match_case: true, whole_words: true };
+        assert!(editor.find("cat", options));
+        assert_eq!(editor.selection(), Some((11, 14)));
+        assert!(!editor.find_next());
+    }
+
+    #[test]
+    fn replaces_all_matches_as_separate_changes() {
+        let mut editor = editor("a-a-a");
+        assert_eq!(editor.replace("a", "bb", TFindOptions::default(), true), 3);
+        assert_eq!(editor.get_text(), "bb-bb-bb");
+        editor.undo();
+        assert_eq!(editor.get_text(), "bb-bb-a");
+    }
+
+    #[test]
+    fn longest_line_follows_edits() {
+        let mut editor = editor("abc\nabcdef\nab");
+        assert_eq!(editor.longest_line(), 6);
+        editor.set_cursor(0, 3);
+        editor.insert_text("1234");
+        assert_eq!(editor.longest_line(), 7);
+        editor.undo();
+        assert_eq!(editor.longest_line(), 6);
+        editor.set_cursor(1, 0);
+        editor.replace_raw(4, 6, "");
+        assert_eq!(editor.longest_line(), 3);
+        editor.tab_size = 4;
+        editor.insert_text("\t\t");
+        assert_eq!(editor.longest_line(), 8);
+    }
+
+    #[test]
+    fn ctrl_h_asks_for_both_texts_then_replaces() {
+        let mut editor = editor("old and old");
+        let queue = TEventQueue::new();
+        editor.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)), &queue);
+        assert!(matches!(queue.get_event(), Some(TEvent::InputBox { .. })));
+        editor.handle_event(TEvent::Reply(CM_OK, "old".to_string()), &queue);
+        assert!(matches!(queue.get_event(), Some(TEvent::InputBox { .. })));
+        editor.handle_event(TEvent::Reply(CM_OK, "new".to_string()), &queue);
+        assert_eq!(editor.get_text(), "new and new");
+
+        editor.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL)), &queue);
+        assert!(matches!(queue.get_event(), Some(TEvent::InputBox { .. })));
+        editor.handle_event(TEvent::Reply(CM_OK, "missing".to_string()), &queue);
+        assert!(matches!(queue.get_event(), Some(TEvent::Error(_))));
+    }
+}
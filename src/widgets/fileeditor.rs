@@ -0,0 +1,328 @@
+use crate::core::command::{CM_CLOSE, CM_NO, CM_OK, CM_OPEN, CM_QUIT, CM_SAVE, CM_SAVE_AS, CM_YES};
+use crate::core::rect::TRect;
+use crate::core::event::{mouse_to_local, TEvent, TEventQueue};
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::editor::TEditor;
+use crossterm::event::KeyEvent;
+use crossterm::style::Color;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const FRAME_FG: Color = Color::White;
+const FRAME_BG: Color = Color::Blue;
+
+/// Line break style of a loaded file, kept when it is saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The style most lines of `text` end with, and whether some lines end
+    /// the other way
+    pub fn detect(text: &str) -> (Self, bool) {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        let ending = if crlf > lf { Self::CrLf } else { Self::Lf };
+        (ending, crlf > 0 && lf > 0)
+    }
+}
+
+/// How the file's bytes map to text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileEncoding {
+    Utf8,
+    /// Files that are not valid UTF-8 are read one char per byte, so every
+    /// byte survives a load and save unchanged
+    Latin1,
+}
+
+/// What to do with the answer to a box the window asked for
+#[derive(Debug, Clone, Copy)]
+enum Pending {
+    SaveAs(Option<u16>),    // File name for Save As; the command to repeat once saved
+    Open,                   // File name to open
+    SaveChanges(u16),       // Whether to save before running the command
+}
+
+/// A framed window editing one file. It handles `CM_SAVE`, `CM_SAVE_AS`
+/// and `CM_OPEN`, shows the file name and a `*` while modified in its
+/// title, and asks to save unsaved changes before `CM_CLOSE`, `CM_QUIT` or
+/// opening another file. `CM_CLOSE` leaves an empty, untitled window.
+/// Saving keeps the old file as `<name>.bak`. Lines are saved with the
+/// line ending most of them had; `mixed_line_endings` tells when some had
+/// the other one.
+pub struct TFileEditor {
+    pub bounds: TRect,
+    pub editor: TEditor,
+    pub file_name: Option<PathBuf>,
+    pub make_backups: bool,
+    pub line_ending: LineEnding,
+    pub mixed_line_endings: bool,   // The loaded file had both kinds of line ending
+    pub encoding: FileEncoding,
+    pending: Option<Pending>,
+}
+
+impl TFileEditor {
+    /// Creates an empty, untitled window
+    pub fn new(bounds: TRect) -> Self {
+        let editor = TEditor::new(TRect::new(0, 0, bounds.width.saturating_sub(2), bounds.height.saturating_sub(2)));
+        Self {
+            bounds,
+            editor,
+            file_name: None,
+            make_backups: true,
+            line_ending: LineEnding::Lf,
+            mixed_line_endings: false,
+            encoding: FileEncoding::Utf8,
+            pending: None,
+        }
+    }
+
+    /// Creates a window editing `path`. A missing file opens empty and is
+    /// created on save.
+    pub fn open(bounds: TRect, path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut window = Self::new(bounds);
+        window.load(path)?;
+        Ok(window)
+    }
+
+    pub fn title(&self) -> String {
+        let name = self
+            .file_name
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or("Untitled".to_string(), |name| name.to_string_lossy().into_owned());
+        if self.editor.modified {
+            format!("{} *", name)
+        } else {
+            name
+        }
+    }
+
+    /// Replaces the text with the contents of `path`
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        let (text, encoding) = match String::from_utf8(bytes) {
+            Ok(text) => (text, FileEncoding::Utf8),
+            Err(err) => (err.into_bytes().iter().map(|&b| b as char).collect(), FileEncoding::Latin1),
+        };
+        (self.line_ending, self.mixed_line_endings) = LineEnding::detect(&text);
+        self.encoding = encoding;
+        self.editor.set_text(&text);
+        self.file_name = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Message warning that saving will change some line endings
+    pub fn mixed_line_endings_warning(&self) -> String {
+        let ending = match self.line_ending {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        };
+        format!("{} has mixed line endings; all lines will be saved with {}", self.title(), ending)
+    }
+
+    /// Empties the window, leaving it untitled
+    pub fn close(&mut self) {
+        self.editor.set_text("");
+        self.file_name = None;
+        self.line_ending = LineEnding::Lf;
+        self.mixed_line_endings = false;
+        self.encoding = FileEncoding::Utf8;
+    }
+
+    /// Writes the text to `file_name`, keeping the previous file as a backup
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(path) = self.file_name.clone() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "no file name"));
+        };
+        let mut text = self.editor.get_text();
+        if self.line_ending == LineEnding::CrLf {
+            text = text.replace('\n', "\r\n");
+        }
+        let bytes = match self.encoding {
+            FileEncoding::Utf8 => text.into_bytes(),
+            FileEncoding::Latin1 => text
+                .chars()
+                .map(|ch| u8::try_from(ch).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "text has chars outside Latin-1")))
+                .collect::<io::Result<Vec<u8>>>()?,
+        };
+        if self.make_backups && path.exists() {
+            let mut backup = path.clone().into_os_string();
+            backup.push(".bak");
+            fs::copy(&path, backup)?;
+        }
+        fs::write(&path, bytes)?;
+        self.editor.set_modified(false);
+        Ok(())
+    }
+
+    /// Saves to `path` and makes it the window's file
+    pub fn save_as(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let previous = self.file_name.replace(path.as_ref().to_path_buf());
+        let result = self.save();
+        if result.is_err() {
+            self.file_name = previous;
+        }
+        result
+    }
+
+    /// Saves, reporting failure as an error event. Returns true on success.
+    fn save_or_report(&mut self, queue: &TEventQueue) -> bool {
+        match self.save() {
+            Ok(()) => true,
+            Err(err) => {
+                queue.put_event(TEvent::Error(format!("Cannot save {}: {}", self.title(), err)));
+                false
+            }
+        }
+    }
+
+    fn ask_file_name(&mut self, title: &str, pending: Pending, queue: &TEventQueue) {
+        let value = self.file_name.as_ref().map_or(String::new(), |path| path.to_string_lossy().into_owned());
+        self.pending = Some(pending);
+        queue.put_event(TEvent::InputBox { title: title.to_string(), label: "File name:".to_string(), value });
+    }
+
+    /// Saves, first asking for a file name if there is none. `then` is
+    /// posted once the file is saved.
+    fn save_then(&mut self, then: Option<u16>, queue: &TEventQueue) {
+        if self.file_name.is_none() {
+            self.ask_file_name("Save As", Pending::SaveAs(then), queue);
+        } else if self.save_or_report(queue) {
+            if let Some(command) = then {
+                queue.put_event(TEvent::Command(command));
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: u16, queue: &TEventQueue) {
+        match command {
+            CM_SAVE => self.save_then(None, queue),
+            CM_SAVE_AS => self.ask_file_name("Save As", Pending::SaveAs(None), queue),
+            CM_OPEN if self.valid(CM_OPEN, queue) => self.ask_file_name("Open", Pending::Open, queue),
+            CM_CLOSE => self.close(),
+            _ => {}
+        }
+    }
+
+    fn handle_reply(&mut self, command: u16, text: String, queue: &TEventQueue) {
+        match (self.pending.take(), command) {
+            (Some(Pending::SaveAs(then)), CM_OK) if !text.trim().is_empty() => {
+                match self.save_as(text.trim()) {
+                    Ok(()) => {
+                        if let Some(command) = then {
+                            queue.put_event(TEvent::Command(command));
+                        }
+                    }
+                    Err(err) => queue.put_event(TEvent::Error(format!("Cannot save {}: {}", text.trim(), err))),
+                }
+            }
+            (Some(Pending::Open), CM_OK) if !text.trim().is_empty() => {
+                match self.load(text.trim()) {
+                    Ok(()) if self.mixed_line_endings => queue.put_event(TEvent::Error(self.mixed_line_endings_warning())),
+                    Ok(()) => {}
+                    Err(err) => queue.put_event(TEvent::Error(format!("Cannot open {}: {}", text.trim(), err))),
+                }
+            }
+            (Some(Pending::SaveChanges(then)), CM_YES) => self.save_then(Some(then), queue),
+            (Some(Pending::SaveChanges(then)), CM_NO) => {
+                // Changes are dropped; the command can go ahead now
+                self.editor.set_modified(false);
+                queue.put_event(TEvent::Command(then));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TView for TFileEditor {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let frame = TRect::new(offset.0 + self.bounds.x, offset.1 + self.bounds.y, self.bounds.width, self.bounds.height);
+        buffer.fill(frame, ' ', FRAME_FG, FRAME_BG);
+        buffer.draw_frame(frame, FRAME_FG, FRAME_BG);
+
+        let title = format!(" {} ", self.title());
+        let title: String = title.chars().take(frame.width.saturating_sub(4) as usize).collect();
+        let title_x = frame.x + frame.width.saturating_sub(title.chars().count() as u16) / 2;
+        buffer.write_styled(title_x, frame.y, &title, FRAME_FG, FRAME_BG);
+
+        self.editor.draw(buffer, (frame.x + 1, frame.y + 1));
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Command(command) => self.handle_command(command, queue),
+            // Other replies answer the editor's find and replace prompts
+            TEvent::Reply(command, text) if self.pending.is_some() => self.handle_reply(command, text, queue),
+            TEvent::Mouse(mouse) => {
+                let local = mouse_to_local(mouse, (self.bounds.x + 1, self.bounds.y + 1));
+                self.editor.handle_event(TEvent::Mouse(local), queue);
+            }
+            other => self.editor.handle_event(other, queue),
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+        self.editor
+            .set_bounds(TRect::new(0, 0, bounds.width.saturating_sub(2), bounds.height.saturating_sub(2)));
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.editor.set_focus(focused);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.editor.help_context()
+    }
+
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        self.editor.takes_key(key)
+    }
+
+    /// Asks whether to save unsaved changes before closing, quitting or
+    /// opening another file. The command is posted again once answered.
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        if !matches!(command, CM_CLOSE | CM_QUIT | CM_OPEN) || !self.editor.modified {
+            return true;
+        }
+        self.pending = Some(Pending::SaveChanges(command));
+        queue.put_event(TEvent::Confirm {
+            title: "Confirm".to_string(),
+            text: format!("Save changes to {}?", self.title().trim_end_matches(" *")),
+        });
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_line_ending_most_lines_use() {
+        assert_eq!(LineEnding::detect("a\nb\n"), (LineEnding::Lf, false));
+        assert_eq!(LineEnding::detect("a\r\nb\r\n"), (LineEnding::CrLf, false));
+        assert_eq!(LineEnding::detect("a\r\nb\nc\n"), (LineEnding::Lf, true));
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), (LineEnding::CrLf, true));
+        assert_eq!(LineEnding::detect("no break"), (LineEnding::Lf, false));
+    }
+}
@@ -39,8 +39,8 @@ impl TView for TFooterBar {
     }
 
     fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
-        match &event {
-            TEvent::Key(key) => match key.code {
+        if let TEvent::Key(key) = &event {
+            match key.code {
                 KeyCode::Tab => {
                     self.focus.focus_next(&mut self.children);
                     return;
@@ -50,8 +50,7 @@ impl TView for TFooterBar {
                     return;
                 }
                 _ => {}
-            },
-            _ => {}
+            }
         }
 
         if let Some(child) = self.focus.current_mut(&mut self.children) {
@@ -76,4 +75,8 @@ impl TView for TFooterBar {
             child.set_focus(focused);
         }
     }
-}
\ No newline at end of file
+
+    fn help_context(&self) -> Option<u16> {
+        self.focus.current(&self.children).and_then(|child| child.help_context())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::core::command::CM_CANCEL;
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::view::TView;
+use crate::help::helpfile::{THelpFile, THelpTopic};
+use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+use std::rc::Rc;
+
+const HELP_FG: Color = Color::Black;
+const HELP_BG: Color = Color::Cyan;
+const LINK_FG: Color = Color::Yellow;
+const SELECTED_LINK_BG: Color = Color::Blue;
+
+/// Position in the help file, kept for the back-history
+#[derive(Clone, Copy)]
+struct HelpPosition {
+    topic: usize,
+    link: Option<usize>,
+    scroll: usize,
+}
+
+/// Framed viewer for a help file. Tab and Shift+Tab move between links,
+/// Enter or a click follows the selected link, Backspace or Alt+Left goes
+/// back, and Esc closes the window by posting `CM_CANCEL`.
+pub struct THelpWindow {
+    pub bounds: TRect,
+    file: Rc<THelpFile>,
+    current: HelpPosition,
+    history: Vec<HelpPosition>,
+    focused: bool,
+}
+
+impl THelpWindow {
+    /// Opens on the topic for `ctx`, or the first topic when there is none
+    pub fn new(file: Rc<THelpFile>, ctx: Option<u16>, bounds: TRect) -> Self {
+        let topic = ctx.and_then(|ctx| file.topic_for_context(ctx)).unwrap_or(0);
+        let mut window = Self {
+            bounds,
+            file,
+            current: HelpPosition { topic, link: None, scroll: 0 },
+            history: Vec::new(),
+            focused: true,
+        };
+        window.current.link = window.first_link();
+        window
+    }
+
+    fn topic(&self) -> Option<&THelpTopic> {
+        self.file.topic(self.current.topic)
+    }
+
+    fn first_link(&self) -> Option<usize> {
+        self.topic().filter(|topic| !topic.links.is_empty()).map(|_| 0)
+    }
+
+    fn page_height(&self) -> usize {
+        self.bounds.height.saturating_sub(2) as usize
+    }
+
+    fn max_scroll(&self) -> usize {
+        let lines = self.topic().map_or(0, |topic| topic.lines.len());
+        lines.saturating_sub(self.page_height())
+    }
+
+    fn scroll_to(&mut self, scroll: usize) {
+        self.current.scroll = scroll.min(self.max_scroll());
+    }
+
+    fn ensure_link_visible(&mut self) {
+        let line = match (self.topic(), self.current.link) {
+            (Some(topic), Some(link)) => topic.links[link].line,
+            _ => return,
+        };
+        if line < self.current.scroll {
+            self.current.scroll = line;
+        } else if line >= self.current.scroll + self.page_height() {
+            self.current.scroll = line + 1 - self.page_height();
+        }
+    }
+
+    fn select_next_link(&mut self, forward: bool) {
+        let count = self.topic().map_or(0, |topic| topic.links.len());
+        if count == 0 {
+            return;
+        }
+        self.current.link = Some(match self.current.link {
+            Some(link) if forward => (link + 1) % count,
+            Some(link) => (link + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        });
+        self.ensure_link_visible();
+    }
+
+    fn follow_link(&mut self, link: usize) {
+        let Some(target) = self.topic().and_then(|topic| topic.links.get(link)).map(|link| link.target) else {
+            return;
+        };
+        self.current.link = Some(link);
+        self.history.push(self.current);
+        self.current = HelpPosition { topic: target, link: None, scroll: 0 };
+        self.current.link = self.first_link();
+    }
+
+    fn go_back(&mut self) {
+        if let Some(previous) = self.history.pop() {
+            self.current = previous;
+        }
+    }
+
+    /// Returns the link under screen point `pt`
+    fn link_at(&self, pt: TPoint) -> Option<usize> {
+        let topic = self.topic()?;
+        let row = pt.y.checked_sub(self.bounds.y + 1)? as usize;
+        if row >= self.page_height() {
+            return None;
+        }
+        let line = self.current.scroll + row;
+        let col = pt.x.checked_sub(self.bounds.x + 1)? as usize;
+        topic
+            .links
+            .iter()
+            .position(|link| link.line == line && col >= link.col && col < link.col + link.len)
+    }
+}
+
+impl TView for THelpWindow {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let frame = TRect::new(
+            offset.0 + self.bounds.x,
+            offset.1 + self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        buffer.fill(frame, ' ', HELP_FG, HELP_BG);
+        buffer.draw_frame(frame, HELP_FG, HELP_BG);
+
+        let Some(topic) = self.topic() else {
+            return;
+        };
+
+        let title = format!(" Help: {} ", topic.name);
+        let title_x = frame.x + frame.width.saturating_sub(title.chars().count() as u16) / 2;
+        buffer.write_styled(title_x, frame.y, &title, HELP_FG, HELP_BG);
+
+        let inner_width = frame.width.saturating_sub(2) as usize;
+        for (row, line) in topic.lines.iter().skip(self.current.scroll).take(self.page_height()).enumerate() {
+            let line_index = self.current.scroll + row;
+            let y = frame.y + 1 + row as u16;
+            for (col, ch) in line.chars().take(inner_width).enumerate() {
+                let link = topic
+                    .links
+                    .iter()
+                    .position(|link| link.line == line_index && col >= link.col && col < link.col + link.len);
+                let (fg, bg) = match link {
+                    Some(link) if self.current.link == Some(link) => (LINK_FG, SELECTED_LINK_BG),
+                    Some(_) => (LINK_FG, HELP_BG),
+                    None => (HELP_FG, HELP_BG),
+                };
+                buffer.set_styled(frame.x + 1 + col as u16, y, ch, fg, bg);
+            }
+        }
+
+        buffer.shadow(frame);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Key(KeyEvent { code, modifiers, .. }) => match code {
+                KeyCode::Tab => self.select_next_link(true),
+                KeyCode::BackTab => self.select_next_link(false),
+                KeyCode::Enter => {
+                    if let Some(link) = self.current.link {
+                        self.follow_link(link);
+                    }
+                }
+                KeyCode::Backspace => self.go_back(),
+                KeyCode::Left if modifiers.contains(KeyModifiers::ALT) => self.go_back(),
+                KeyCode::Up => self.scroll_to(self.current.scroll.saturating_sub(1)),
+                KeyCode::Down => self.scroll_to(self.current.scroll + 1),
+                KeyCode::PageUp => self.scroll_to(self.current.scroll.saturating_sub(self.page_height())),
+                KeyCode::PageDown => self.scroll_to(self.current.scroll + self.page_height()),
+                KeyCode::Home => self.scroll_to(0),
+                KeyCode::End => self.scroll_to(self.max_scroll()),
+                KeyCode::Esc => queue.put_event(TEvent::Command(CM_CANCEL)),
+                _ => {}
+            },
+            TEvent::Mouse(mouse) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) if !self.bounds.contains(pt) => {
+                        queue.put_event(TEvent::Command(CM_CANCEL));
+                    }
+                    MouseEventKind::Up(MouseButton::Left) => {
+                        if let Some(link) = self.link_at(pt) {
+                            self.follow_link(link);
+                        }
+                    }
+                    MouseEventKind::ScrollUp => self.scroll_to(self.current.scroll.saturating_sub(1)),
+                    MouseEventKind::ScrollDown => self.scroll_to(self.current.scroll + 1),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::command::CM_CANCEL;
+use crate::core::history::THistoryStore;
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::view::{TData, TView};
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::inputline::TInputLine;
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+use std::cell::Cell;
+
+const ICON_FG: Color = Color::Green;
+const ARROW_FG: Color = Color::Black;
+const ARROW_BG: Color = Color::Green;
+
+/// Width of the `▐↓▌` icon drawn after the input line
+const ICON_WIDTH: u16 = 3;
+
+/// An input line with a history list. The list holds values entered
+/// before under the same history ID and opens with Down or a click on the
+/// arrow icon; picking an entry fills the input line. The current value is
+/// added to the list when focus leaves or the dialog is accepted.
+pub struct THistory {
+    pub input: TInputLine,
+    pub history_id: u16,
+    store: THistoryStore,
+    origin: Cell<(u16, u16)>, // screen position of the last draw
+}
+
+impl THistory {
+    /// Adds the icon to the right of `input`
+    pub fn new(input: TInputLine, history_id: u16) -> Self {
+        Self {
+            input,
+            history_id,
+            store: THistoryStore::shared(),
+            origin: Cell::new((0, 0)),
+        }
+    }
+
+    /// Uses `store` instead of the application history
+    pub fn set_store(&mut self, store: THistoryStore) {
+        self.store = store;
+    }
+
+    pub fn get_value(&self) -> &str {
+        self.input.get_value()
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.input.set_value(value);
+    }
+
+    fn icon_bounds(&self) -> TRect {
+        let input = self.input.bounds;
+        TRect::new(input.x + input.width, input.y, ICON_WIDTH, 1)
+    }
+
+    /// Asks the application to show the entries below the input line
+    fn open_list(&self, queue: &TEventQueue) {
+        let entries = self.store.entries(self.history_id);
+        if entries.is_empty() {
+            return;
+        }
+        let (x, y) = self.origin.get();
+        let at = TRect::new(x, y, self.input.bounds.width + ICON_WIDTH, 1);
+        let selected = entries.iter().position(|entry| entry == self.input.get_value()).unwrap_or(0);
+        queue.put_event(TEvent::PopupList(entries, at, selected));
+    }
+}
+
+impl TView for THistory {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        self.origin.set((offset.0 + self.input.bounds.x, offset.1 + self.input.bounds.y));
+        self.input.draw(buffer, offset);
+
+        let icon = self.icon_bounds();
+        let x = offset.0 + icon.x;
+        let y = offset.1 + icon.y;
+        let bg = buffer.get(x, y).map_or(Color::Reset, |cell| cell.bg);
+        buffer.set_styled(x, y, '▐', ICON_FG, bg);
+        buffer.set_styled(x + 1, y, '↓', ARROW_FG, ARROW_BG);
+        buffer.set_styled(x + 2, y, '▌', ICON_FG, bg);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Key(KeyEvent { code: KeyCode::Down, .. }) => self.open_list(queue),
+            TEvent::Mouse(mouse)
+                if mouse.kind == MouseEventKind::Down(MouseButton::Left)
+                    && self.icon_bounds().contains(TPoint { x: mouse.column, y: mouse.row }) =>
+            {
+                self.open_list(queue);
+            }
+            TEvent::ListChoice(index) => {
+                if let Some(entry) = self.store.entries(self.history_id).get(index) {
+                    self.input.set_value(entry);
+                    self.input.select_all();
+                }
+            }
+            other => self.input.handle_event(other, queue),
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        let input = self.input.bounds;
+        TRect::new(input.x, input.y, input.width + ICON_WIDTH, input.height)
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        let width = bounds.width.saturating_sub(ICON_WIDTH);
+        self.input.set_bounds(TRect::new(bounds.x, bounds.y, width, bounds.height));
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.input.set_focus(focused);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.input.help_context()
+    }
+
+    fn get_data(&self) -> Option<TData> {
+        self.input.get_data()
+    }
+
+    fn set_data(&mut self, data: TData) {
+        self.input.set_data(data);
+    }
+
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        if !self.input.valid(command, queue) {
+            return false;
+        }
+        if command != CM_CANCEL {
+            self.store.add(self.history_id, self.input.get_value());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command::CM_OK;
+    use crossterm::event::KeyModifiers;
+
+    fn history(store: &THistoryStore) -> THistory {
+        let mut history = THistory::new(TInputLine::new(TRect::new(0, 0, 20, 1)), 5);
+        history.set_store(store.clone());
+        history
+    }
+
+    #[test]
+    fn value_is_recorded_unless_cancelled() {
+        let queue = TEventQueue::new();
+        let store = THistoryStore::new();
+        let mut history = history(&store);
+        history.set_value("kept");
+        assert!(history.valid(CM_OK, &queue));
+        history.set_value("dropped");
+        assert!(history.valid(CM_CANCEL, &queue));
+        assert_eq!(store.entries(5), vec!["kept"]);
+    }
+
+    #[test]
+    fn list_opens_on_current_value_and_fills_input() {
+        let queue = TEventQueue::new();
+        let store = THistoryStore::new();
+        for value in ["old", "middle", "new"] {
+            store.add(5, value);
+        }
+        let mut history = history(&store);
+        history.set_value("middle");
+        history.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)), &queue);
+        match queue.get_event() {
+            Some(TEvent::PopupList(entries, _, selected)) => {
+                assert_eq!(entries, vec!["new", "middle", "old"]);
+                assert_eq!(selected, 1);
+            }
+            other => panic!("expected a popup list, got {:?}", other),
+        }
+
+        history.handle_event(TEvent::ListChoice(2), &queue);
+        assert_eq!(history.get_value(), "old");
+    }
+}
@@ -1,14 +1,53 @@
-use crate::core::rect::TRect;
+use crate::core::clipboard::TClipboard;
+use crate::core::command::CM_CANCEL;
+use crate::core::rect::{TPoint, TRect};
 use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::view::{TData, TView};
 use crate::ui::screenbuffer::ScreenBuffer;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::widgets::validator::Validator;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+use std::mem;
 
+const INPUT_FG: Color = Color::White;
+const INPUT_BG: Color = Color::Blue;
+const SELECTED_FG: Color = Color::Black;
+const SELECTED_BG: Color = Color::Cyan;
+const ARROW_FG: Color = Color::Green;
+const CURSOR_FG: Color = Color::Blue;
+const CURSOR_BG: Color = Color::White;
+const OVERWRITE_CURSOR_BG: Color = Color::Yellow;
+
+/// Chars a masked input line holds before its buffer has to grow
+const MASKED_CAPACITY: usize = 256;
+
+/// Single-line text field. The cursor, selection and scroll position count
+/// chars, not bytes. Arrows at either end show that the text runs past the
+/// visible part. An optional validator checks edits as they are made and the
+/// whole value when focus leaves or the dialog is accepted. In masked mode
+/// the text is drawn as mask chars, cannot be copied or cut, never grows
+/// past the buffer allocated for it and is overwritten with zeros as it is
+/// edited, cleared or dropped. Masked text is not handed out as dialog data.
+///
+/// The text is not a public field: read it with `get_value` and change it
+/// with `set_value`, which keep the cursor, `max_len` and the masked buffer
+/// consistent.
 pub struct TInputLine {
-    pub text: String,
+    text: String,
     pub bounds: TRect,
-    pub cursor_pos: usize,
+    pub cursor_pos: usize,          // Cursor position, in chars
     pub focused: bool,
+    pub help_context: Option<u16>,
+    pub max_len: Option<usize>,     // Longest accepted text, in chars
+    pub overwrite: bool,            // Typing replaces the char under the cursor
+    sel_anchor: Option<usize>,      // Other end of the selection, in chars
+    first_pos: usize,               // First visible char
+    clipboard: TClipboard,
+    validator: Option<Box<dyn Validator>>,
+    mask: Option<char>,                          // Char drawn for each char of masked text
+    reveal_key: Option<(KeyCode, KeyModifiers)>, // Key toggling masked text visible
+    revealed: bool,
+    mouse_down: bool,
 }
 
 impl TInputLine {
@@ -18,80 +57,465 @@ impl TInputLine {
             bounds,
             cursor_pos: 0,
             focused: true,
+            help_context: None,
+            max_len: None,
+            overwrite: false,
+            sel_anchor: None,
+            first_pos: 0,
+            clipboard: TClipboard::shared(),
+            validator: None,
+            mask: None,
+            reveal_key: None,
+            revealed: false,
+            mouse_down: false,
+        }
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    /// Limits the text to `max_len` chars, truncating the current value
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+        if let Some(max) = max_len {
+            if self.char_count() > max {
+                self.text.truncate(self.byte_index(max));
+                self.zeroize_spare();
+                self.cursor_pos = self.cursor_pos.min(max);
+                self.sel_anchor = None;
+                self.scroll_to_cursor();
+            }
+        }
+    }
+
+    /// Uses `clipboard` for cut, copy and paste instead of the application one
+    pub fn set_clipboard(&mut self, clipboard: TClipboard) {
+        self.clipboard = clipboard;
+    }
+
+    pub fn set_validator<V: Validator + 'static>(&mut self, validator: V) {
+        self.validator = Some(Box::new(validator));
+    }
+
+    /// Turns masked mode on, drawing each char as `mask`, or off with `None`
+    pub fn set_mask(&mut self, mask: Option<char>) {
+        self.mask = mask;
+        self.revealed = false;
+        if mask.is_some() {
+            // Allocate up front; masked text never grows past this, so no
+            // copies are left behind in reallocated memory
+            let mut buffer = String::with_capacity(self.max_len.unwrap_or(0).max(MASKED_CAPACITY) * 4);
+            buffer.push_str(&self.text);
+            zeroize(&mut mem::replace(&mut self.text, buffer));
         }
     }
 
+    /// Sets the key that shows and hides masked text
+    pub fn set_reveal_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        self.reveal_key = Some((key, modifiers));
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.mask.is_some() && !self.revealed
+    }
+
+    /// Empties the text, overwriting the old contents with zeros
+    pub fn clear(&mut self) {
+        zeroize(&mut self.text);
+        self.cursor_pos = 0;
+        self.sel_anchor = None;
+        self.first_pos = 0;
+    }
+
+    /// The text, masked or not
     pub fn get_value(&self) -> &str {
         &self.text
     }
 
+    /// Replaces the text, cut to `max_len`, with the cursor at its end
     pub fn set_value(&mut self, value: &str) {
-        self.text = value.to_string();
-        self.cursor_pos = self.text.len();
+        self.clear();
+        self.insert_str(value);
+        self.scroll_to_cursor();
     }
-}
 
-impl TView for TInputLine {
-    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
-        let x = offset.0 + self.bounds.x;
-        let y = offset.1 + self.bounds.y;
-        let width = self.bounds.width as usize;
+    /// Returns the selected char range, if any text is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.sel_anchor?;
+        match anchor.cmp(&self.cursor_pos) {
+            std::cmp::Ordering::Less => Some((anchor, self.cursor_pos)),
+            std::cmp::Ordering::Greater => Some((self.cursor_pos, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        let (start, end) = self.selection()?;
+        Some(&self.text[self.byte_index(start)..self.byte_index(end)])
+    }
 
-        // Ensure display is exactly width characters
-        let mut line = self.text.clone();
-        if line.len() > width {
-            line = line[self.cursor_pos.saturating_sub(width - 1)..self.cursor_pos].to_string();
+    pub fn select_all(&mut self) {
+        self.sel_anchor = Some(0);
+        self.cursor_pos = self.char_count();
+        self.scroll_to_cursor();
+    }
+
+    /// Copy of the text with the same capacity, so a validator completing
+    /// it does not reallocate and leave a copy behind in freed memory
+    fn copy_text(&self) -> String {
+        let mut copy = String::with_capacity(self.text.capacity());
+        copy.push_str(&self.text);
+        copy
+    }
+
+    /// Bytes masked text can still grow by without reallocating
+    fn spare_bytes(&self) -> usize {
+        match self.mask {
+            Some(_) => self.text.capacity() - self.text.len(),
+            None => usize::MAX,
+        }
+    }
+
+    /// Wipes masked text left in the buffer's spare capacity by deletions
+    fn zeroize_spare(&mut self) {
+        if self.mask.is_some() {
+            zeroize_spare(&mut self.text);
+        }
+    }
+
+    fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Byte offset of char `pos`, or the text length past the end
+    fn byte_index(&self, pos: usize) -> usize {
+        self.text.char_indices().nth(pos).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Number of columns available for text, between the scroll arrows
+    fn text_width(&self) -> usize {
+        self.bounds.width.saturating_sub(2) as usize
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let width = self.text_width().max(1);
+        if self.cursor_pos < self.first_pos {
+            self.first_pos = self.cursor_pos;
+        } else if self.cursor_pos >= self.first_pos + width {
+            self.first_pos = self.cursor_pos + 1 - width;
+        }
+        // Don't leave empty space at the end while text is scrolled off the left
+        let max_first = (self.char_count() + 1).saturating_sub(width);
+        self.first_pos = self.first_pos.min(max_first);
+    }
+
+    /// Moves the cursor, extending the selection when `extend` is set
+    fn move_cursor(&mut self, pos: usize, extend: bool) {
+        if extend {
+            self.sel_anchor.get_or_insert(self.cursor_pos);
         } else {
-            line = format!("{:<width$}", line, width = width);
+            self.sel_anchor = None;
         }
+        self.cursor_pos = pos.min(self.char_count());
+    }
 
-        buffer.write_str(x, y, &line);
+    fn delete_range(&mut self, start: usize, end: usize) {
+        let range = self.byte_index(start)..self.byte_index(end);
+        self.text.replace_range(range, "");
+        self.cursor_pos = start;
+        self.sel_anchor = None;
+    }
 
-        // Optional: show cursor (if focused and within bounds)
-        if self.focused {
-            let cursor_x = x + self.cursor_pos.min(self.bounds.width as usize) as u16;
-            if cursor_x < x + self.bounds.width {
-                buffer.set(cursor_x, y, '_');
+    /// Deletes the selected text, returning true if there was any
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.delete_range(start, end);
+                true
             }
+            None => false,
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
-        if let TEvent::Key(KeyEvent { code, .. }) = event {
-            match code {
-                KeyCode::Char(c) => {
-                    self.text.insert(self.cursor_pos, c);
-                    self.cursor_pos += 1;
-                }
-                KeyCode::Backspace => {
-                    if self.cursor_pos > 0 {
-                        self.cursor_pos -= 1;
-                        self.text.remove(self.cursor_pos);
-                    }
-                }
-                KeyCode::Delete => {
-                    if self.cursor_pos < self.text.len() {
-                        self.text.remove(self.cursor_pos);
-                    }
-                }
-                KeyCode::Left => {
-                    if self.cursor_pos > 0 {
-                        self.cursor_pos -= 1;
-                    }
-                }
-                KeyCode::Right => {
-                    if self.cursor_pos < self.text.len() {
-                        self.cursor_pos += 1;
-                    }
+    /// Inserts `text` at the cursor, replacing the selection and keeping to
+    /// `max_len`. Control chars such as pasted newlines are dropped.
+    fn insert_str(&mut self, text: &str) {
+        self.delete_selection();
+        let room = self.max_len.map_or(usize::MAX, |max| max.saturating_sub(self.char_count()));
+        let mut spare = self.spare_bytes();
+        let mut s = String::with_capacity(text.len());
+        for ch in text.chars().filter(|c| !c.is_control()).take(room) {
+            if ch.len_utf8() > spare {
+                break;
+            }
+            spare -= ch.len_utf8();
+            s.push(ch);
+        }
+        let at = self.byte_index(self.cursor_pos);
+        self.text.insert_str(at, &s);
+        self.cursor_pos += s.chars().count();
+        zeroize(&mut s);
+    }
+
+    fn type_char(&mut self, ch: char) {
+        if self.overwrite && self.selection().is_none() && self.cursor_pos < self.char_count() {
+            let at = self.byte_index(self.cursor_pos);
+            let len = self.text[at..].chars().next().map_or(0, char::len_utf8);
+            if ch.len_utf8().saturating_sub(len) > self.spare_bytes() {
+                return;
+            }
+            self.text.replace_range(at..at + len, ch.encode_utf8(&mut [0; 4]));
+            self.cursor_pos += 1;
+        } else {
+            self.insert_str(ch.encode_utf8(&mut [0; 4]));
+        }
+    }
+
+    fn copy(&self) {
+        if self.mask.is_some() {
+            return;
+        }
+        if let Some(text) = self.selected_text() {
+            self.clipboard.set_text(text);
+        }
+    }
+
+    fn cut(&mut self) {
+        if self.mask.is_some() {
+            return;
+        }
+        self.copy();
+        self.delete_selection();
+    }
+
+    fn paste(&mut self) {
+        let text = self.clipboard.text();
+        self.insert_str(&text);
+    }
+
+    /// Start of the word before char `pos`. Masked text counts as one word
+    /// so its word breaks are not given away.
+    fn prev_word(&self, pos: usize) -> usize {
+        if self.mask.is_some() {
+            return 0;
+        }
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = pos.min(chars.len());
+        while pos > 0 && !is_word_char(chars[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > 0 && is_word_char(chars[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// Start of the word after char `pos`
+    fn next_word(&self, pos: usize) -> usize {
+        if self.mask.is_some() {
+            return self.char_count();
+        }
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = pos.min(chars.len());
+        while pos < chars.len() && is_word_char(chars[pos]) {
+            pos += 1;
+        }
+        while pos < chars.len() && !is_word_char(chars[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Runs the validator over an edit that changed `old` into the current
+    /// text, applying any completion it makes. Returns false if rejected.
+    fn check_input(&mut self, old: &str) -> bool {
+        let Some(validator) = &self.validator else {
+            return true;
+        };
+        let mut text = self.copy_text();
+        let suppress_fill = text.chars().count() < old.chars().count();
+        // Masked text must still fit its buffer after any completion
+        let accepted = validator.is_valid_input(&mut text, suppress_fill)
+            && (self.mask.is_none() || text.len() <= self.text.capacity());
+        if !accepted {
+            zeroize(&mut text);
+            return false;
+        }
+        if text != self.text {
+            let at_end = self.cursor_pos == self.char_count();
+            self.text.clear();
+            self.text.push_str(&text);
+            self.cursor_pos = if at_end { self.char_count() } else { self.cursor_pos.min(self.char_count()) };
+        }
+        zeroize(&mut text);
+        true
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.mask.is_some() && self.reveal_key == Some((key.code, key.modifiers)) {
+            self.revealed = !self.revealed;
+            return;
+        }
+        let (mut old_text, old_cursor, old_anchor) = (self.copy_text(), self.cursor_pos, self.sel_anchor);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.select_all(),
+            KeyCode::Char('c') if ctrl => self.copy(),
+            KeyCode::Char('x') if ctrl => self.cut(),
+            KeyCode::Char('v') if ctrl => self.paste(),
+            KeyCode::Char(c) if !ctrl && !alt => self.type_char(c),
+            KeyCode::Backspace if ctrl || alt => {
+                if !self.delete_selection() {
+                    self.delete_range(self.prev_word(self.cursor_pos), self.cursor_pos);
                 }
-                KeyCode::End => {
-                    self.cursor_pos = self.text.len();
+            }
+            KeyCode::Backspace => {
+                if !self.delete_selection() && self.cursor_pos > 0 {
+                    self.delete_range(self.cursor_pos - 1, self.cursor_pos);
                 }
-                KeyCode::Home => {
-                    self.cursor_pos = 0;
+            }
+            KeyCode::Delete if shift => self.cut(),
+            KeyCode::Delete => {
+                if !self.delete_selection() && self.cursor_pos < self.char_count() {
+                    self.delete_range(self.cursor_pos, self.cursor_pos + 1);
                 }
-                _ => {}
             }
+            KeyCode::Insert if ctrl => self.copy(),
+            KeyCode::Insert if shift => self.paste(),
+            KeyCode::Insert => self.overwrite = !self.overwrite,
+            KeyCode::Left if ctrl => self.move_cursor(self.prev_word(self.cursor_pos), shift),
+            KeyCode::Right if ctrl => self.move_cursor(self.next_word(self.cursor_pos), shift),
+            KeyCode::Left => self.move_cursor(self.cursor_pos.saturating_sub(1), shift),
+            KeyCode::Right => self.move_cursor(self.cursor_pos + 1, shift),
+            KeyCode::Home => self.move_cursor(0, shift),
+            KeyCode::End => self.move_cursor(self.char_count(), shift),
+            _ => {
+                zeroize(&mut old_text);
+                return;
+            }
+        }
+        if self.text != old_text && !self.check_input(&old_text) {
+            self.text.clear();
+            self.text.push_str(&old_text);
+            self.cursor_pos = old_cursor;
+            self.sel_anchor = old_anchor;
+        }
+        zeroize(&mut old_text);
+        self.zeroize_spare();
+        self.scroll_to_cursor();
+    }
+
+    /// Char position under mouse column `column`, scrolling when the pointer
+    /// is on or past an arrow
+    fn pos_at(&mut self, column: u16) -> usize {
+        let left = self.bounds.x + 1;
+        if column < left {
+            self.first_pos = self.first_pos.saturating_sub(1);
+            return self.first_pos;
+        }
+        let col = (column - left) as usize;
+        if col >= self.text_width() {
+            return (self.first_pos + self.text_width()).min(self.char_count());
+        }
+        (self.first_pos + col).min(self.char_count())
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let inside = self.bounds.contains(TPoint { x: mouse.column, y: mouse.row });
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if inside => {
+                let shift = mouse.modifiers.contains(KeyModifiers::SHIFT);
+                let pos = self.pos_at(mouse.column);
+                self.move_cursor(pos, shift);
+                self.sel_anchor.get_or_insert(pos);
+                self.mouse_down = true;
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.mouse_down => {
+                let pos = self.pos_at(mouse.column);
+                self.move_cursor(pos, true);
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.mouse_down = false,
+            _ => return,
+        }
+        self.scroll_to_cursor();
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Overwrites the whole buffer of `s`, spare capacity included, with zeros
+/// and empties it
+fn zeroize(s: &mut String) {
+    s.clear();
+    zeroize_spare(s);
+}
+
+/// Overwrites the buffer of `s` past its length with zeros
+fn zeroize_spare(s: &mut String) {
+    let mut bytes = mem::take(s).into_bytes();
+    let len = bytes.len();
+    bytes.resize(bytes.capacity(), 0);
+    // Keep the writes from being optimized away as dead stores
+    std::hint::black_box(&bytes);
+    bytes.truncate(len);
+    // Hand the buffer back so its capacity is reused
+    *s = String::from_utf8(bytes).unwrap_or_default();
+}
+
+impl Drop for TInputLine {
+    fn drop(&mut self) {
+        zeroize(&mut self.text);
+    }
+}
+
+impl TView for TInputLine {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        let width = self.text_width();
+
+        buffer.fill(TRect::new(x, y, self.bounds.width, 1), ' ', INPUT_FG, INPUT_BG);
+
+        let selection = self.selection();
+        let mask = if self.is_masked() { self.mask } else { None };
+        for (col, ch) in self.text.chars().skip(self.first_pos).take(width).enumerate() {
+            let pos = self.first_pos + col;
+            let ch = mask.unwrap_or(ch);
+            let (fg, bg) = match selection {
+                Some((start, end)) if pos >= start && pos < end => (SELECTED_FG, SELECTED_BG),
+                _ => (INPUT_FG, INPUT_BG),
+            };
+            buffer.set_styled(x + 1 + col as u16, y, ch, fg, bg);
+        }
+
+        if self.bounds.width < 2 {
+            return;
+        }
+        if self.first_pos > 0 {
+            buffer.set_styled(x, y, '◄', ARROW_FG, INPUT_BG);
+        }
+        if self.char_count() > self.first_pos + width {
+            buffer.set_styled(x + self.bounds.width - 1, y, '►', ARROW_FG, INPUT_BG);
+        }
+
+        if self.focused && self.cursor_pos >= self.first_pos && self.cursor_pos < self.first_pos + width {
+            let cursor_x = x + 1 + (self.cursor_pos - self.first_pos) as u16;
+            let ch = buffer.get(cursor_x, y).map_or(' ', |cell| cell.ch);
+            let bg = if self.overwrite { OVERWRITE_CURSOR_BG } else { CURSOR_BG };
+            buffer.set_styled(cursor_x, y, ch, CURSOR_FG, bg);
+        }
+    }
+
+    fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
+        match event {
+            TEvent::Key(key) => self.handle_key(key),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
         }
     }
 
@@ -101,12 +525,82 @@ impl TView for TInputLine {
 
     fn set_bounds(&mut self, bounds: TRect) {
         self.bounds = bounds;
+        self.scroll_to_cursor();
     }
 
     fn set_focus(&mut self, focused: bool) {
         self.focused = focused;
+        if !focused {
+            self.mouse_down = false;
+            self.revealed = false;
+        }
     }
     fn is_focusable(&self) -> bool {
         true
     }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    /// The text, unless masked: a copy handed out could not be wiped, so
+    /// masked text is read with `get_value` instead
+    fn get_data(&self) -> Option<TData> {
+        match self.mask {
+            Some(_) => None,
+            None => Some(TData::Text(self.text.clone())),
+        }
+    }
+
+    fn set_data(&mut self, data: TData) {
+        if let TData::Text(text) = data {
+            self.set_value(&text);
+        }
+    }
+
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        match &self.validator {
+            Some(validator) if command != CM_CANCEL && !validator.is_valid(&self.text) => {
+                queue.put_event(TEvent::Error(validator.error_message()));
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(line: &mut TInputLine, text: &str) {
+        for ch in text.chars() {
+            line.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn masked_text_never_outgrows_its_buffer() {
+        let mut line = TInputLine::new(TRect::new(0, 0, 20, 1));
+        line.set_mask(Some('*'));
+        let capacity = line.text.capacity();
+
+        type_text(&mut line, &"é".repeat(capacity));
+        assert_eq!(line.text.capacity(), capacity);
+        assert_eq!(line.get_value().len(), capacity);
+
+        line.set_value(&"x".repeat(capacity + 10));
+        assert_eq!(line.text.capacity(), capacity);
+        assert_eq!(line.get_value().len(), capacity);
+    }
+
+    #[test]
+    fn masked_text_is_not_handed_out_as_data() {
+        let mut line = TInputLine::new(TRect::new(0, 0, 20, 1));
+        type_text(&mut line, "secret");
+        assert_eq!(line.get_data(), Some(TData::Text("secret".to_string())));
+        line.set_mask(Some('*'));
+        assert_eq!(line.get_data(), None);
+        assert_eq!(line.get_value(), "secret");
+    }
 }
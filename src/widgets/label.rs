@@ -32,7 +32,7 @@ impl TView for TLabel {
         buffer.write_str(x, y, &line);
     }
 
-    fn handle_event(&mut self, _event: TEvent, queue: &TEventQueue) {}
+    fn handle_event(&mut self, _event: TEvent, _queue: &TEventQueue) {}
 
     fn get_bounds(&self) -> TRect {
         self.bounds
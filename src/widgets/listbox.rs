@@ -1,103 +1,564 @@
-use crate::core::rect::TRect;
+use crate::core::rect::{TPoint, TRect};
 use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::input::{DOUBLE_CLICK_TIME, SEARCH_TIMEOUT, WHEEL_STEP};
+use crate::core::view::{TData, TView};
 use crate::ui::screenbuffer::ScreenBuffer;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::widgets::listsource::{ListDataSource, TListSource};
+use crate::core::menu::TMenu;
+use crate::widgets::scrollbar::ScrollBars;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashSet};
+use std::rc::Rc;
+use std::time::Instant;
 
+const MARKED_FG: Color = Color::Black;
+const MARKED_BG: Color = Color::Cyan;
+
+type SharedSource = Rc<RefCell<dyn ListDataSource>>;
+
+/// How a list box finds its selection again after its data changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepSelection {
+    /// Stay at the same index, moved back if the list got shorter
+    ByIndex,
+    /// Follow the selected item's key, or fall back to the index
+    ByKey,
+}
+
+/// A list of items drawn from a `ListDataSource`. Only the visible items
+/// are read, and changes to a shared `TListSource` are picked up without
+/// rebuilding the list box.
+///
+/// With more than one column, items flow down each column and on into the
+/// next, like TurboVision's `TListViewer`. In multi-select mode Space and
+/// Ctrl+click mark single items and Shift with a move or click marks a
+/// range. Typing jumps to the first item starting with the typed text.
+/// Changes to the focused item or the marks post `ListSelectionChanged`,
+/// and Enter or a double-click posts `ListItemActivated`.
 pub struct TListBox {
-    pub items: Vec<String>,
     pub bounds: TRect,
-    pub selected_index: usize,
-    pub scroll_offset: usize,
+    pub selected_index: usize,      // Focused item
+    pub scroll_offset: usize,       // First visible item
     pub focused: bool,
+    pub help_context: Option<u16>,
+    pub context_menu: Option<TMenu>,
+    pub keep_selection: KeepSelection,
+    pub list_id: u16,               // Sent with the list's events to tell lists apart
+    pub columns: u16,
+    pub multi_select: bool,
+    source: SharedSource,
+    revision: Rc<Cell<u64>>,
+    seen_revision: u64,
+    selected_key: Option<String>,   // Key of the focused item when the data was last seen
+    marked: BTreeSet<usize>,        // Selected items in multi-select mode
+    marked_keys: Vec<String>,       // Their keys when the data was last seen
+    anchor: usize,                  // Fixed end of a Shift range
+    search: String,
+    last_search: Option<Instant>,
+    last_click: Option<(Instant, usize)>,
+    scroll_bars: ScrollBars,
+    origin: Cell<(u16, u16)>, // screen position of the last draw
 }
 
 impl TListBox {
     pub fn new(bounds: TRect, items: Vec<&str>) -> Self {
-        Self {
-            items: items.into_iter().map(String::from).collect(),
+        let items: Vec<String> = items.into_iter().map(String::from).collect();
+        Self::with_source(bounds, &TListSource::new(items))
+    }
+
+    /// Creates a list box showing the shared `source`
+    pub fn with_source<S: ListDataSource + 'static>(bounds: TRect, source: &TListSource<S>) -> Self {
+        let mut list = Self {
             bounds,
             selected_index: 0,
             scroll_offset: 0,
             focused: true,
+            help_context: None,
+            context_menu: None,
+            keep_selection: KeepSelection::ByKey,
+            list_id: 0,
+            columns: 1,
+            multi_select: false,
+            source: source.data(),
+            revision: source.revision(),
+            seen_revision: 0,
+            selected_key: None,
+            marked: BTreeSet::new(),
+            marked_keys: Vec::new(),
+            anchor: 0,
+            search: String::new(),
+            last_search: None,
+            last_click: None,
+            scroll_bars: ScrollBars::default(),
+            origin: Cell::new((0, 0)),
+        };
+        list.seen_revision = list.revision.get();
+        list.remember_selection();
+        list
+    }
+
+    /// Shows `source` instead, starting from the first item
+    pub fn set_source<S: ListDataSource + 'static>(&mut self, source: &TListSource<S>) {
+        self.source = source.data();
+        self.revision = source.revision();
+        self.seen_revision = self.revision.get();
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.anchor = 0;
+        self.marked.clear();
+        self.remember_selection();
+        self.sync_scroll_bar();
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    pub fn set_list_id(&mut self, id: u16) {
+        self.list_id = id;
+    }
+
+    /// Sets the menu shown on right-click or Shift+F10
+    pub fn set_context_menu(&mut self, menu: TMenu) {
+        self.context_menu = Some(menu);
+    }
+
+    /// Shows or hides a scroll bar in the right column
+    pub fn set_scroll_bar(&mut self, show: bool) {
+        self.scroll_bars.show(show, false);
+        self.layout_scroll_bar();
+    }
+
+    /// Flows the items into `columns` columns
+    pub fn set_columns(&mut self, columns: u16) {
+        self.columns = columns.max(1);
+        self.ensure_visible();
+    }
+
+    /// Turns multi-select mode on or off; turning it off clears the marks
+    pub fn set_multi_select(&mut self, multi_select: bool) {
+        self.multi_select = multi_select;
+        if !multi_select {
+            self.marked.clear();
+            self.marked_keys.clear();
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.source.borrow().count()
+    }
+
+    pub fn item_text(&self, index: usize) -> Option<String> {
+        let source = self.source.borrow();
+        (index < source.count()).then(|| source.item_text(index))
+    }
+
+    pub fn selected_item(&self) -> Option<String> {
+        self.item_text(self.selected_index)
+    }
+
+    /// The marked items in multi-select mode, otherwise the focused item
+    pub fn selected_indices(&self) -> Vec<usize> {
+        if self.multi_select {
+            self.marked.iter().copied().collect()
+        } else if self.selected_index < self.count() {
+            vec![self.selected_index]
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        if self.multi_select {
+            self.marked.contains(&index)
+        } else {
+            index == self.selected_index
+        }
+    }
+
+    /// Focuses the item at `index` and scrolls it into view
+    pub fn select(&mut self, index: usize) {
+        self.refresh();
+        if index < self.count() {
+            self.selected_index = index;
+            self.anchor = index;
+            self.ensure_visible();
+            self.remember_selection();
+        }
+    }
+
+    /// Marks or unmarks the item at `index` in multi-select mode
+    pub fn set_marked(&mut self, index: usize, marked: bool) {
+        if !self.multi_select || index >= self.count() {
+            return;
+        }
+        if marked {
+            self.marked.insert(index);
+        } else {
+            self.marked.remove(&index);
         }
+        self.remember_selection();
     }
 
-    pub fn selected_item(&self) -> Option<&str> {
-        self.items.get(self.selected_index).map(String::as_str)
+    /// Catches up with changes to the data: the selection is found again
+    /// as `keep_selection` says and the scroll position is kept in range.
+    /// Done before each event; call it after changing the data to update
+    /// the list box at once.
+    pub fn refresh(&mut self) {
+        if self.revision.get() == self.seen_revision {
+            return;
+        }
+        (self.selected_index, self.scroll_offset) = self.resolved_position();
+        self.anchor = self.selected_index;
+        self.marked = self.resolved_marks();
+        self.seen_revision = self.revision.get();
+        self.remember_selection();
+        self.sync_scroll_bar();
+    }
+
+    /// Focused index and scroll offset for the current data, without
+    /// storing them
+    fn resolved_position(&self) -> (usize, usize) {
+        if self.revision.get() == self.seen_revision {
+            return (self.selected_index, self.scroll_offset);
+        }
+        let source = self.source.borrow();
+        let count = source.count();
+        let by_key = match (&self.selected_key, self.keep_selection) {
+            (Some(key), KeepSelection::ByKey) => source.index_of_key(key),
+            _ => None,
+        };
+        let selected = by_key.unwrap_or(self.selected_index.min(count.saturating_sub(1)));
+        let offset = self.scroll_offset.min(count.saturating_sub(self.page_size()));
+        (selected, self.offset_showing(selected, offset))
+    }
+
+    /// Marked items for the current data, found by key or by index
+    fn resolved_marks(&self) -> BTreeSet<usize> {
+        if self.revision.get() == self.seen_revision {
+            return self.marked.clone();
+        }
+        let source = self.source.borrow();
+        let count = source.count();
+        if self.keep_selection == KeepSelection::ByKey && !self.marked_keys.is_empty() {
+            let keys: HashSet<&str> = self.marked_keys.iter().map(String::as_str).collect();
+            source.indices_of_keys(&keys)
+        } else {
+            self.marked.iter().copied().filter(|&index| index < count).collect()
+        }
+    }
+
+    fn remember_selection(&mut self) {
+        let source = self.source.borrow();
+        self.selected_key = source.item_key(self.selected_index);
+        self.marked_keys = self.marked.iter().filter_map(|&index| source.item_key(index)).collect();
+    }
+
+    fn layout_scroll_bar(&mut self) {
+        self.scroll_bars.layout(self.scroll_bars.content_area(self.bounds));
+        self.sync_scroll_bar();
+    }
+
+    /// Moves the scroll bar to the current scroll position
+    fn sync_scroll_bar(&mut self) {
+        let (page, max) = (self.page_size(), self.max_scroll_offset());
+        self.scroll_bars.sync((0, self.scroll_offset), (0, max), (0, page));
+    }
+
+    fn max_scroll_offset(&self) -> usize {
+        self.count().saturating_sub(self.page_size())
+    }
+
+    /// Columns left for the items
+    fn item_width(&self) -> usize {
+        self.scroll_bars.content_area(self.bounds).width as usize
+    }
+
+    /// Width of one column, not counting the separator after it
+    fn column_width(&self) -> usize {
+        let columns = self.columns.max(1) as usize;
+        self.item_width().saturating_sub(columns - 1) / columns
     }
 
+    fn screen_bounds(&self) -> TRect {
+        let (x, y) = self.origin.get();
+        TRect::new(x, y, self.bounds.width, self.bounds.height)
+    }
+
+    /// Local column and row of the item at `index`, if it is visible
+    fn item_cell(&self, index: usize) -> Option<(u16, u16)> {
+        let rows = self.visible_items().max(1);
+        let pos = index.checked_sub(self.scroll_offset).filter(|&pos| pos < self.page_size())?;
+        let col = pos / rows * (self.column_width() + 1);
+        Some((col as u16, (pos % rows) as u16))
+    }
+
+    /// Index of the item at local point `col`, `row`, if there is one
+    fn item_at(&self, col: u16, row: u16) -> Option<usize> {
+        let column = col as usize / (self.column_width() + 1);
+        if column >= self.columns.max(1) as usize || row as usize >= self.visible_items() {
+            return None;
+        }
+        let index = self.scroll_offset + column * self.visible_items() + row as usize;
+        (index < self.count()).then_some(index)
+    }
+
+    /// Screen position just below the start of the focused item
+    fn selection_point(&self) -> TPoint {
+        let bounds = self.screen_bounds();
+        let (col, row) = self.item_cell(self.selected_index).unwrap_or((0, 0));
+        TPoint { x: bounds.x + col + 2, y: bounds.y + row + 1 }
+    }
+
+    fn open_context_menu(&self, at: TPoint, queue: &TEventQueue) {
+        if let Some(menu) = &self.context_menu {
+            queue.put_event(TEvent::PopupMenu(menu.clone(), at));
+        }
+    }
+
+    /// Rows in each column
     fn visible_items(&self) -> usize {
         self.bounds.height as usize
     }
 
+    /// Items shown at once, over all columns
+    fn page_size(&self) -> usize {
+        self.visible_items() * self.columns.max(1) as usize
+    }
+
+    /// Scroll offset closest to `offset` that shows `index`. With several
+    /// columns the list scrolls by whole columns.
+    fn offset_showing(&self, index: usize, offset: usize) -> usize {
+        let rows = self.visible_items().max(1);
+        let columns = self.columns.max(1) as usize;
+        if index < offset {
+            if columns == 1 { index } else { index - index % rows }
+        } else if index >= offset + self.page_size() {
+            if columns == 1 {
+                index + 1 - rows
+            } else {
+                (index - index % rows).saturating_sub((columns - 1) * rows)
+            }
+        } else {
+            offset
+        }
+    }
+
     fn ensure_visible(&mut self) {
-        if self.selected_index < self.scroll_offset {
-            self.scroll_offset = self.selected_index;
-        } else if self.selected_index >= self.scroll_offset + self.visible_items() {
-            self.scroll_offset = self.selected_index - self.visible_items() + 1;
+        self.scroll_offset = self.offset_showing(self.selected_index, self.scroll_offset);
+        self.sync_scroll_bar();
+    }
+
+    /// Moves the focus to `index`. With `extend` in multi-select mode the
+    /// items from the anchor to `index` become the marked ones.
+    fn move_focus(&mut self, index: usize, extend: bool) {
+        self.selected_index = index;
+        if extend && self.multi_select {
+            let (start, end) = (self.anchor.min(index), self.anchor.max(index));
+            self.marked = (start..=end).collect();
+        } else {
+            self.anchor = index;
         }
+        self.ensure_visible();
     }
-}
 
-impl TView for TListBox {
-    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
-        let x = offset.0 + self.bounds.x;
-        let y = offset.1 + self.bounds.y;
+    fn toggle_mark(&mut self, index: usize) {
+        if !self.marked.remove(&index) {
+            self.marked.insert(index);
+        }
+    }
+
+    fn activate(&self, index: usize, queue: &TEventQueue) {
+        if index < self.count() {
+            queue.put_event(TEvent::ListItemActivated(self.list_id, index));
+        }
+    }
+
+    /// Adds `ch` to the search text and focuses the first item starting
+    /// with it. A char that matches nothing is dropped.
+    fn search_for(&mut self, ch: char) {
+        if self.last_search.is_some_and(|at| at.elapsed() > SEARCH_TIMEOUT) {
+            self.search.clear();
+        }
+        self.last_search = Some(Instant::now());
+        self.search.push(ch);
+        match self.find_prefix(&self.search) {
+            Some(index) => self.move_focus(index, false),
+            None => {
+                self.search.pop();
+            }
+        }
+    }
 
-        let visible_count = self.visible_items();
-        let lines = self.items.iter()
-            .skip(self.scroll_offset)
-            .take(visible_count);
+    fn find_prefix(&self, prefix: &str) -> Option<usize> {
+        self.source.borrow().find_prefix(prefix)
+    }
 
-        for (i, item) in lines.enumerate() {
-            let global_index = self.scroll_offset + i;
-            let prefix = if self.focused && global_index == self.selected_index {
-                "▶ "
-            } else {
-                "  "
-            };
-            let display = format!("{}{}", prefix, item);
-            let line = if display.len() > self.bounds.width as usize {
-                display[..self.bounds.width as usize].to_string()
-            } else {
-                format!("{:<width$}", display, width = self.bounds.width as usize)
-            };
+    fn handle_key(&mut self, key: KeyEvent, queue: &TEventQueue) {
+        let KeyEvent { code, modifiers, .. } = key;
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let count = self.count();
+        let last = count.saturating_sub(1);
+        let current = self.selected_index;
+        let rows = self.visible_items().max(1);
+        let multi_column = self.columns > 1;
 
-            if (i as u16) < self.bounds.height {
-                buffer.write_str(x, y + i as u16, &line);
+        let target = match code {
+            KeyCode::Up => Some(current.saturating_sub(1)),
+            KeyCode::Down => Some((current + 1).min(last)),
+            KeyCode::Left if multi_column => Some(current.saturating_sub(rows)),
+            KeyCode::Right if multi_column => Some((current + rows).min(last)),
+            KeyCode::PageUp => Some(current.saturating_sub(self.page_size())),
+            KeyCode::PageDown => Some((current + self.page_size()).min(last)),
+            KeyCode::Home => Some(0),
+            KeyCode::End => Some(last),
+            _ => None,
+        };
+        if let Some(index) = target {
+            self.search.clear();
+            if count > 0 {
+                self.move_focus(index, shift);
             }
+            return;
+        }
+
+        match code {
+            KeyCode::F(10) if shift => self.open_context_menu(self.selection_point(), queue),
+            KeyCode::Menu => self.open_context_menu(self.selection_point(), queue),
+            KeyCode::Enter => self.activate(current, queue),
+            KeyCode::Char(' ') if self.multi_select && self.search.is_empty() && current < count => {
+                self.toggle_mark(current);
+                self.anchor = current;
+            }
+            KeyCode::Char(ch) if plain => self.search_for(ch),
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            _ => {}
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
-        if let TEvent::Key(KeyEvent { code, .. }) = event {
-            match code {
-                KeyCode::Up => {
-                    if self.selected_index > 0 {
-                        self.selected_index -= 1;
-                        self.ensure_visible();
+    fn handle_mouse(&mut self, mouse: MouseEvent, queue: &TEventQueue) {
+        let pt = TPoint { x: mouse.column, y: mouse.row };
+        if let Some((_, offset)) = self.scroll_bars.handle_mouse(&mouse, (0, self.scroll_offset)) {
+            self.scroll_offset = offset;
+            return;
+        }
+        if !self.bounds.contains(pt) {
+            return;
+        }
+        let wheel_step = if self.columns > 1 { self.visible_items() } else { WHEEL_STEP };
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(index) = self.item_at(pt.x - self.bounds.x, pt.y - self.bounds.y) else {
+                    return;
+                };
+                self.search.clear();
+                if self.multi_select && mouse.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.toggle_mark(index);
+                    self.move_focus(index, false);
+                } else if self.multi_select && mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                    self.move_focus(index, true);
+                } else {
+                    self.move_focus(index, false);
+                    if self.multi_select {
+                        self.marked = BTreeSet::from([index]);
                     }
-                }
-                KeyCode::Down => {
-                    if self.selected_index + 1 < self.items.len() {
-                        self.selected_index += 1;
-                        self.ensure_visible();
+                    let double = self
+                        .last_click
+                        .is_some_and(|(at, clicked)| clicked == index && at.elapsed() <= DOUBLE_CLICK_TIME);
+                    if double {
+                        self.last_click = None;
+                        self.activate(index, queue);
+                        return;
                     }
                 }
-                KeyCode::PageUp => {
-                    let n = self.visible_items().min(self.selected_index);
-                    self.selected_index -= n;
-                    self.ensure_visible();
+                self.last_click = Some((Instant::now(), index));
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                let screen = self.screen_bounds();
+                let at = TPoint {
+                    x: screen.x + pt.x - self.bounds.x,
+                    y: screen.y + pt.y - self.bounds.y,
+                };
+                self.open_context_menu(at, queue);
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(wheel_step);
+                self.sync_scroll_bar();
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_offset = (self.scroll_offset + wheel_step).min(self.max_scroll_offset());
+                self.sync_scroll_bar();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TView for TListBox {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        self.origin.set((x, y));
+
+        let (selected_index, scroll_offset) = self.resolved_position();
+        let marked = if self.multi_select { self.resolved_marks() } else { BTreeSet::new() };
+        let source = self.source.borrow();
+        let count = source.count();
+        let rows = self.visible_items();
+        let width = self.column_width();
+
+        for column in 0..self.columns.max(1) as usize {
+            let col_x = x + (column * (width + 1)) as u16;
+            for row in 0..rows {
+                let index = scroll_offset + column * rows + row;
+                if index >= count {
+                    break;
+                }
+                let prefix = if self.focused && index == selected_index {
+                    "▶ "
+                } else {
+                    "  "
+                };
+                let display: String = format!("{}{}", prefix, source.item_text(index)).chars().take(width).collect();
+                let line = format!("{:<width$}", display, width = width);
+
+                let style = if marked.contains(&index) {
+                    Some((MARKED_FG, MARKED_BG))
+                } else {
+                    source.item_style(index)
+                };
+                match style {
+                    Some((fg, bg)) => buffer.write_styled(col_x, y + row as u16, &line, fg, bg),
+                    None => buffer.write_str(col_x, y + row as u16, &line),
                 }
-                KeyCode::PageDown => {
-                    let n = self.visible_items();
-                    self.selected_index = (self.selected_index + n).min(self.items.len() - 1);
-                    self.ensure_visible();
+            }
+            if column + 1 < self.columns as usize {
+                for row in 0..rows {
+                    buffer.set(col_x + width as u16, y + row as u16, '│');
                 }
-                _ => {}
             }
         }
+
+        self.scroll_bars.draw(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        self.refresh();
+        let (index_before, marked_before) = (self.selected_index, self.marked.clone());
+        match event {
+            TEvent::Key(key) => self.handle_key(key, queue),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse, queue),
+            _ => {}
+        }
+        if self.selected_index != index_before || self.marked != marked_before {
+            queue.put_event(TEvent::ListSelectionChanged(self.list_id, self.selected_index));
+        }
+        self.remember_selection();
     }
 
     fn get_bounds(&self) -> TRect {
@@ -106,6 +567,7 @@ impl TView for TListBox {
 
     fn set_bounds(&mut self, bounds: TRect) {
         self.bounds = bounds;
+        self.layout_scroll_bar();
     }
 
     fn set_focus(&mut self, focused: bool) {
@@ -114,4 +576,86 @@ impl TView for TListBox {
     fn is_focusable(&self) -> bool {
         true
     }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    /// Enter activates the focused item
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Enter
+    }
+
+    /// The focused item's index, or which items are marked in
+    /// multi-select mode
+    fn get_data(&self) -> Option<TData> {
+        if self.multi_select {
+            let marks = self.resolved_marks();
+            Some(TData::Checked((0..self.count()).map(|index| marks.contains(&index)).collect()))
+        } else {
+            Some(TData::Index(self.resolved_position().0))
+        }
+    }
+
+    fn set_data(&mut self, data: TData) {
+        match data {
+            TData::Index(index) => self.select(index),
+            TData::Checked(marks) if self.multi_select => {
+                self.refresh();
+                self.marked = marks.iter().enumerate().filter(|(_, &marked)| marked).map(|(index, _)| index).collect();
+                self.remember_selection();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(items: &[&str]) -> TListSource<Vec<String>> {
+        TListSource::new(items.iter().map(|item| item.to_string()).collect())
+    }
+
+    fn list(source: &TListSource<Vec<String>>, keep_selection: KeepSelection) -> TListBox {
+        let mut list = TListBox::with_source(TRect::new(0, 0, 20, 2), source);
+        list.keep_selection = keep_selection;
+        list.set_multi_select(true);
+        list.select(2);
+        list.set_marked(1, true);
+        list.set_marked(2, true);
+        list
+    }
+
+    #[test]
+    fn selection_follows_keys_when_items_are_inserted() {
+        let items = source(&["a", "b", "c", "d"]);
+        let list = list(&items, KeepSelection::ByKey);
+        items.update(|items| items.insert(0, "new".to_string()));
+
+        assert_eq!(list.resolved_position(), (3, 2));
+        assert_eq!(list.resolved_marks(), BTreeSet::from([2, 3]));
+    }
+
+    #[test]
+    fn selection_keeps_its_index_when_items_are_inserted() {
+        let items = source(&["a", "b", "c", "d"]);
+        let list = list(&items, KeepSelection::ByIndex);
+        items.update(|items| items.insert(0, "new".to_string()));
+
+        assert_eq!(list.resolved_position(), (2, 1));
+        assert_eq!(list.resolved_marks(), BTreeSet::from([1, 2]));
+    }
+
+    #[test]
+    fn selection_moves_back_when_the_list_shrinks() {
+        let items = source(&["a", "b", "c", "d"]);
+        let mut list = list(&items, KeepSelection::ByIndex);
+        items.update(|items| items.truncate(2));
+        list.refresh();
+
+        assert_eq!((list.selected_index, list.scroll_offset), (1, 0));
+        assert_eq!(list.selected_indices(), vec![1]);
+    }
 }
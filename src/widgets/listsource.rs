@@ -0,0 +1,110 @@
+use crossterm::style::Color;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::{BTreeSet, HashSet};
+use std::rc::Rc;
+
+/// Items shown by a `TListBox`. Only the visible items are asked for, so
+/// the data can be large or computed on demand.
+pub trait ListDataSource {
+    fn count(&self) -> usize;
+
+    fn item_text(&self, index: usize) -> String;
+
+    /// Foreground and background colors for the item, if not the default
+    fn item_style(&self, _index: usize) -> Option<(Color, Color)> {
+        None
+    }
+
+    /// A value identifying the item across changes to the data, used to
+    /// keep it selected when items are added or removed before it
+    fn item_key(&self, _index: usize) -> Option<String> {
+        None
+    }
+
+    /// Index of the item with `key`. The default asks every item for its
+    /// key; data with an index of its own can answer without that.
+    fn index_of_key(&self, key: &str) -> Option<usize> {
+        (0..self.count()).find(|&index| self.item_key(index).as_deref() == Some(key))
+    }
+
+    /// Indices of the items whose keys are among `keys`, found in one pass
+    /// by default
+    fn indices_of_keys(&self, keys: &HashSet<&str>) -> BTreeSet<usize> {
+        (0..self.count())
+            .filter(|&index| self.item_key(index).is_some_and(|key| keys.contains(key.as_str())))
+            .collect()
+    }
+
+    /// Index of the first item whose text starts with `prefix`, ignoring
+    /// case; used by type-ahead search
+    fn find_prefix(&self, prefix: &str) -> Option<usize> {
+        let prefix = prefix.to_lowercase();
+        (0..self.count()).find(|&index| self.item_text(index).to_lowercase().starts_with(&prefix))
+    }
+}
+
+impl ListDataSource for Vec<String> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn item_text(&self, index: usize) -> String {
+        self.get(index).cloned().unwrap_or_default()
+    }
+
+    fn item_key(&self, index: usize) -> Option<String> {
+        self.get(index).cloned()
+    }
+}
+
+/// Shared handle to a list's data. List boxes showing it keep a clone;
+/// changes made through `update`, or announced with `changed`, make them
+/// refresh and re-find their selection.
+pub struct TListSource<S> {
+    data: Rc<RefCell<S>>,
+    revision: Rc<Cell<u64>>,
+}
+
+impl<S> Clone for TListSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            data: Rc::clone(&self.data),
+            revision: Rc::clone(&self.revision),
+        }
+    }
+}
+
+impl<S: ListDataSource> TListSource<S> {
+    pub fn new(data: S) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(data)),
+            revision: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn borrow(&self) -> Ref<'_, S> {
+        self.data.borrow()
+    }
+
+    /// Changes the data and tells the list boxes showing it
+    pub fn update<R>(&self, change: impl FnOnce(&mut S) -> R) -> R {
+        let result = change(&mut self.data.borrow_mut());
+        self.changed();
+        result
+    }
+
+    /// Tells the list boxes showing the data that it changed
+    pub fn changed(&self) {
+        self.revision.set(self.revision.get() + 1);
+    }
+
+    pub(crate) fn revision(&self) -> Rc<Cell<u64>> {
+        Rc::clone(&self.revision)
+    }
+}
+
+impl<S: ListDataSource + 'static> TListSource<S> {
+    pub(crate) fn data(&self) -> Rc<RefCell<dyn ListDataSource>> {
+        self.data.clone()
+    }
+}
@@ -0,0 +1,81 @@
+use crate::core::rect::TRect;
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::view::{TData, TView};
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::editor::TEditor;
+use crossterm::event::KeyEvent;
+use crossterm::style::Color;
+
+const MEMO_FG: Color = Color::Black;
+const MEMO_BG: Color = Color::Cyan;
+
+/// A `TEditor` used as a dialog control. Its text is the control's data,
+/// and Tab moves to the next control instead of indenting.
+pub struct TMemo {
+    pub editor: TEditor,
+}
+
+impl TMemo {
+    pub fn new(bounds: TRect) -> Self {
+        let mut editor = TEditor::new(bounds);
+        editor.set_colors(MEMO_FG, MEMO_BG);
+        editor.focused = false;
+        Self { editor }
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.editor.set_help_context(ctx);
+    }
+
+    pub fn get_value(&self) -> String {
+        self.editor.get_text()
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.editor.set_text(value);
+    }
+}
+
+impl TView for TMemo {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        self.editor.draw(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        self.editor.handle_event(event, queue);
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.editor.get_bounds()
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.editor.set_bounds(bounds);
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.editor.set_focus(focused);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.editor.help_context()
+    }
+
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        self.editor.takes_key(key)
+    }
+
+    fn get_data(&self) -> Option<TData> {
+        Some(TData::Text(self.editor.get_text()))
+    }
+
+    fn set_data(&mut self, data: TData) {
+        if let TData::Text(text) = data {
+            self.editor.set_text(&text);
+        }
+    }
+}
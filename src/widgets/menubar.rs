@@ -3,6 +3,7 @@ use crate::core::event::{TEvent, TEventQueue};
 use crate::core::view::TView;
 use crate::ui::screenbuffer::ScreenBuffer;
 use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::style::Color;
 
 #[derive(Debug, Clone)]
 pub struct MenuItem {
@@ -101,7 +102,7 @@ impl TView for TMenuBar {
                 };
 
                 if selected {
-                    buffer.set(x, y0, rendered);
+                    buffer.set_styled(x, y0, rendered, Color::Black, Color::Green);
                 } else {
                     buffer.set(x, y0, rendered);
                 }
@@ -121,7 +122,7 @@ impl TView for TMenuBar {
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+    fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
         if let TEvent::Key(KeyEvent { code, .. }) = event {
             match code {
                 KeyCode::F(10) => {
@@ -1,6 +1,7 @@
 pub mod label;
 pub mod button;
 pub mod inputline;
+pub mod validator;
 pub mod dialog;
 pub mod checkboxes;
 pub mod radiobuttons;
@@ -9,4 +10,18 @@ pub mod footerbar;
 pub mod statusline;
 pub mod menubar;
 pub mod menu;
-pub mod dropdownmenu;
\ No newline at end of file
+pub mod dropdownmenu;
+pub mod helpwindow;
+pub mod popuplist;
+pub mod history;
+pub mod editor;
+pub mod memo;
+pub mod fileeditor;
+pub mod scrollbar;
+pub mod scroller;
+pub mod listsource;
+pub mod table;
+pub mod treeview;
+pub mod combobox;
+pub mod tabview;
+pub mod progress;
@@ -0,0 +1,224 @@
+use crate::core::command::{CM_CANCEL, CM_OK};
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+
+const LIST_FG: Color = Color::Black;
+const LIST_BG: Color = Color::Cyan;
+const SELECTED_FG: Color = Color::White;
+const SELECTED_BG: Color = Color::Green;
+
+/// Framed drop-down list run modally by `TApplication::popup_list`.
+/// Enter or a click picks the selected item and posts `CM_OK`; Esc or a
+/// click outside posts `CM_CANCEL`. Typing narrows the list to the items
+/// starting with the text typed, shown in the bottom of the frame, and
+/// Backspace widens it again; a key that would leave nothing is ignored.
+pub struct TPopupList {
+    pub items: Vec<String>,
+    pub bounds: TRect,
+    pub selected_index: usize,  // Position of the selected item among those shown
+    filter: String,
+    shown: Vec<usize>,          // Items starting with `filter`
+    scroll_offset: usize,
+    mouse_down: bool,
+}
+
+impl TPopupList {
+    pub fn new(items: Vec<String>, bounds: TRect) -> Self {
+        let shown = (0..items.len()).collect();
+        Self {
+            items,
+            bounds,
+            selected_index: 0,
+            filter: String::new(),
+            shown,
+            scroll_offset: 0,
+            mouse_down: false,
+        }
+    }
+
+    /// Opens the list with the item at `index` selected
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.select(index);
+        self
+    }
+
+    /// Index in `items` of the selected item
+    pub fn selected(&self) -> Option<usize> {
+        self.shown.get(self.selected_index).copied()
+    }
+
+    /// Shows only the items starting with `filter`, keeping the selected
+    /// item if it is still shown. Returns false, changing nothing, if no
+    /// item matches.
+    fn set_filter(&mut self, filter: String) -> bool {
+        let prefix = filter.to_lowercase();
+        let shown: Vec<usize> = (0..self.items.len())
+            .filter(|&index| self.items[index].to_lowercase().starts_with(&prefix))
+            .collect();
+        if shown.is_empty() {
+            return false;
+        }
+        let selected = self.selected();
+        self.filter = filter;
+        self.shown = shown;
+        self.scroll_offset = 0;
+        let position = selected.and_then(|selected| self.shown.iter().position(|&index| index == selected));
+        self.select(position.unwrap_or(0));
+        true
+    }
+
+    fn page_height(&self) -> usize {
+        self.bounds.height.saturating_sub(2) as usize
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected_index = index.min(self.shown.len().saturating_sub(1));
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.page_height() {
+            self.scroll_offset = self.selected_index + 1 - self.page_height().max(1);
+        }
+    }
+
+    /// Returns the item at screen point `pt`
+    fn item_at(&self, pt: TPoint) -> Option<usize> {
+        let inner = TRect::new(self.bounds.x + 1, self.bounds.y + 1, self.bounds.width.saturating_sub(2), self.page_height() as u16);
+        if !inner.contains(pt) {
+            return None;
+        }
+        let index = self.scroll_offset + (pt.y - inner.y) as usize;
+        (index < self.shown.len()).then_some(index)
+    }
+}
+
+impl TView for TPopupList {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let frame = TRect::new(offset.0 + self.bounds.x, offset.1 + self.bounds.y, self.bounds.width, self.bounds.height);
+        buffer.fill(frame, ' ', LIST_FG, LIST_BG);
+        buffer.draw_frame(frame, LIST_FG, LIST_BG);
+
+        let inner_width = frame.width.saturating_sub(2) as usize;
+        for (row, &index) in self.shown.iter().skip(self.scroll_offset).take(self.page_height()).enumerate() {
+            let item = &self.items[index];
+            let (fg, bg) = if self.scroll_offset + row == self.selected_index {
+                (SELECTED_FG, SELECTED_BG)
+            } else {
+                (LIST_FG, LIST_BG)
+            };
+            let y = frame.y + 1 + row as u16;
+            buffer.fill(TRect::new(frame.x + 1, y, inner_width as u16, 1), ' ', fg, bg);
+            let text: String = item.chars().take(inner_width.saturating_sub(1)).collect();
+            buffer.write_styled(frame.x + 2, y, &text, fg, bg);
+        }
+
+        if !self.filter.is_empty() {
+            let text: String = self.filter.chars().take(inner_width.saturating_sub(3)).collect();
+            buffer.write_styled(frame.x + 2, frame.y + frame.height - 1, &format!(" {} ", text), LIST_FG, LIST_BG);
+        }
+
+        buffer.shadow(frame);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        match event {
+            TEvent::Key(KeyEvent { code, modifiers, .. }) => match code {
+                KeyCode::Up => self.select(self.selected_index.saturating_sub(1)),
+                KeyCode::Down => self.select(self.selected_index + 1),
+                KeyCode::PageUp => self.select(self.selected_index.saturating_sub(self.page_height())),
+                KeyCode::PageDown => self.select(self.selected_index + self.page_height()),
+                KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(self.shown.len()),
+                KeyCode::Enter => queue.put_event(TEvent::Command(CM_OK)),
+                KeyCode::Esc => queue.put_event(TEvent::Command(CM_CANCEL)),
+                KeyCode::Char(ch) if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                    let filter = format!("{}{}", self.filter, ch);
+                    self.set_filter(filter);
+                }
+                KeyCode::Backspace if !self.filter.is_empty() => {
+                    let mut filter = self.filter.clone();
+                    filter.pop();
+                    self.set_filter(filter);
+                }
+                _ => {}
+            },
+            TEvent::Mouse(mouse) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                match mouse.kind {
+                    MouseEventKind::Down(_) if !self.bounds.contains(pt) => {
+                        queue.put_event(TEvent::Command(CM_CANCEL));
+                    }
+                    MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+                        self.mouse_down = true;
+                        if let Some(index) = self.item_at(pt) {
+                            self.select(index);
+                        }
+                    }
+                    MouseEventKind::Up(MouseButton::Left) if self.mouse_down => {
+                        self.mouse_down = false;
+                        if self.item_at(pt).is_some() {
+                            queue.put_event(TEvent::Command(CM_OK));
+                        }
+                    }
+                    MouseEventKind::ScrollUp => self.select(self.selected_index.saturating_sub(1)),
+                    MouseEventKind::ScrollDown => self.select(self.selected_index + 1),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, _focused: bool) {}
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn popup() -> TPopupList {
+        let items = ["apple", "banana", "blueberry", "cherry"].iter().map(|item| item.to_string()).collect();
+        TPopupList::new(items, TRect::new(0, 0, 20, 6))
+    }
+
+    fn press(list: &mut TPopupList, code: KeyCode) {
+        list.handle_event(TEvent::Key(KeyEvent::new(code, KeyModifiers::NONE)), &TEventQueue::new());
+    }
+
+    #[test]
+    fn opens_on_the_selected_item() {
+        assert_eq!(popup().with_selected(2).selected(), Some(2));
+    }
+
+    #[test]
+    fn typing_narrows_the_list_and_backspace_widens_it() {
+        let mut list = popup().with_selected(3);
+        press(&mut list, KeyCode::Char('B'));
+        assert_eq!(list.shown, vec![1, 2]);
+        assert_eq!(list.selected(), Some(1));
+
+        press(&mut list, KeyCode::Down);
+        press(&mut list, KeyCode::Char('x'));
+        assert_eq!(list.filter, "B");
+        assert_eq!(list.selected(), Some(2));
+
+        press(&mut list, KeyCode::Backspace);
+        assert_eq!(list.shown.len(), 4);
+        assert_eq!(list.selected(), Some(2));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::core::rect::TRect;
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::timer::TimerId;
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::style::Color;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const BAR_FG: Color = Color::Cyan;
+const BAR_BG: Color = Color::Blue;
+const TEXT_FG: Color = Color::White;
+const SPINNER_FG: Color = Color::Yellow;
+
+/// Blocks filling one to seven eighths of a cell from the left
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Width of the percentage text after the bar, e.g. ` 42%`
+const PERCENT_WIDTH: u16 = 5;
+
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Time each spinner frame is shown
+const SPINNER_STEP: Duration = Duration::from_millis(100);
+
+/// Shared handle to the value of a progress bar. The code doing the work
+/// keeps a clone and sets the value as it goes; the bar shows it at the
+/// application's next draw, which only sends the cells that changed.
+#[derive(Clone)]
+pub struct TProgressValue {
+    value: Rc<Cell<u64>>,
+    max: Rc<Cell<u64>>,
+}
+
+impl TProgressValue {
+    pub fn new(max: u64) -> Self {
+        Self {
+            value: Rc::new(Cell::new(0)),
+            max: Rc::new(Cell::new(max)),
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value.get()
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max.get()
+    }
+
+    /// Sets the value, up to `max`
+    pub fn set(&self, value: u64) {
+        self.value.set(value.min(self.max.get()));
+    }
+
+    /// Adds `step` to the value, up to `max`
+    pub fn advance(&self, step: u64) {
+        self.set(self.value.get().saturating_add(step));
+    }
+
+    pub fn set_max(&self, max: u64) {
+        self.max.set(max);
+        self.set(self.value.get());
+    }
+
+    /// Part done, from 0.0 to 1.0; an empty range counts as done
+    pub fn fraction(&self) -> f64 {
+        match self.max.get() {
+            0 => 1.0,
+            max => self.value.get() as f64 / max as f64,
+        }
+    }
+}
+
+/// One-line bar filled in proportion to a `TProgressValue`, in eighths of
+/// a cell, with the percentage done after it
+pub struct TProgressBar {
+    pub bounds: TRect,
+    pub show_percent: bool,
+    progress: TProgressValue,
+}
+
+impl TProgressBar {
+    pub fn new(bounds: TRect, progress: &TProgressValue) -> Self {
+        Self {
+            bounds,
+            show_percent: true,
+            progress: progress.clone(),
+        }
+    }
+
+    pub fn set_show_percent(&mut self, show: bool) {
+        self.show_percent = show;
+    }
+
+    /// Handle for updating the bar
+    pub fn progress(&self) -> TProgressValue {
+        self.progress.clone()
+    }
+}
+
+impl TView for TProgressBar {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        let percent_width = if self.show_percent { PERCENT_WIDTH.min(self.bounds.width) } else { 0 };
+        let width = self.bounds.width - percent_width;
+        let fraction = self.progress.fraction();
+
+        let eighths = (fraction * f64::from(width) * 8.0).round() as u32;
+        for col in 0..width {
+            let filled = eighths.saturating_sub(u32::from(col) * 8).min(8);
+            let ch = match filled {
+                0 => ' ',
+                8 => '█',
+                part => PARTIAL_BLOCKS[part as usize - 1],
+            };
+            buffer.set_styled(x + col, y, ch, BAR_FG, BAR_BG);
+        }
+
+        if percent_width > 0 {
+            let text = format!("{:>4}%", (fraction * 100.0).floor() as u32);
+            let text: String = text.chars().skip((PERCENT_WIDTH - percent_width) as usize).collect();
+            buffer.write_styled(x + width, y, &text, TEXT_FG, Color::Reset);
+        }
+    }
+
+    fn handle_event(&mut self, _event: TEvent, _queue: &TEventQueue) {}
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, _focused: bool) {}
+}
+
+/// Shared handle starting and stopping a `TSpinner`
+#[derive(Clone)]
+pub struct TSpinnerState {
+    started: Rc<Cell<Option<Instant>>>,
+    timer: Rc<Cell<Option<TimerId>>>,   // Wakes the application for each frame
+}
+
+impl TSpinnerState {
+    /// Starts the spinner, with a timer on `queue` that makes the
+    /// application draw each new frame
+    pub fn start(&self, queue: &TEventQueue) {
+        if self.started.get().is_none() {
+            self.started.set(Some(Instant::now()));
+            self.timer.set(Some(queue.set_repeating_timer(SPINNER_STEP)));
+        }
+    }
+
+    /// Stops the spinner and kills its timer on `queue`
+    pub fn stop(&self, queue: &TEventQueue) {
+        self.started.set(None);
+        if let Some(id) = self.timer.take() {
+            queue.kill_timer(id);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.get().is_some()
+    }
+}
+
+/// Activity indicator for work of unknown length: a spinning mark followed
+/// by a message. While running, a repeating timer wakes the application
+/// every `SPINNER_STEP` so it draws the next frame; stopped, only the
+/// message is shown.
+pub struct TSpinner {
+    pub bounds: TRect,
+    pub text: String,
+    state: TSpinnerState,
+}
+
+impl TSpinner {
+    pub fn new(text: &str, bounds: TRect) -> Self {
+        Self {
+            bounds,
+            text: text.to_string(),
+            state: TSpinnerState { started: Rc::new(Cell::new(None)), timer: Rc::new(Cell::new(None)) },
+        }
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+
+    /// Handle for starting and stopping the spinner
+    pub fn state(&self) -> TSpinnerState {
+        self.state.clone()
+    }
+
+    fn frame(&self) -> Option<char> {
+        let started = self.state.started.get()?;
+        let step = started.elapsed().as_millis() / SPINNER_STEP.as_millis();
+        Some(SPINNER_FRAMES[step as usize % SPINNER_FRAMES.len()])
+    }
+}
+
+impl TView for TSpinner {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        buffer.fill(TRect::new(x, y, self.bounds.width, 1), ' ', TEXT_FG, Color::Reset);
+        if let Some(frame) = self.frame() {
+            buffer.set_styled(x, y, frame, SPINNER_FG, Color::Reset);
+        }
+        let text: String = self.text.chars().take(self.bounds.width.saturating_sub(2) as usize).collect();
+        buffer.write_styled(x + 2, y, &text, TEXT_FG, Color::Reset);
+    }
+
+    fn handle_event(&mut self, _event: TEvent, _queue: &TEventQueue) {}
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, _focused: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_running_spinner_keeps_a_timer() {
+        let queue = TEventQueue::new();
+        let state = TSpinner::new("Working", TRect::new(0, 0, 20, 1)).state();
+        state.start(&queue);
+        state.start(&queue);
+        let deadline = queue.next_timer_deadline().expect("no timer while running");
+        assert!(deadline <= Instant::now() + SPINNER_STEP);
+
+        state.stop(&queue);
+        assert!(!state.is_running());
+        assert_eq!(queue.next_timer_deadline(), None);
+    }
+}
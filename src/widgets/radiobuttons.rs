@@ -1,6 +1,6 @@
 use crate::core::rect::TRect;
 use crate::core::event::{TEvent, TEventQueue};
-use crate::core::view::TView;
+use crate::core::view::{TData, TView};
 use crate::ui::screenbuffer::ScreenBuffer;
 use crossterm::event::{KeyCode, KeyEvent};
 
@@ -10,6 +10,7 @@ pub struct TRadioButtons {
     pub bounds: TRect,
     pub focus_index: usize,
     pub focused: bool,
+    pub help_context: Option<u16>,
 }
 
 impl TRadioButtons {
@@ -20,9 +21,14 @@ impl TRadioButtons {
             focus_index: 0,
             bounds,
             focused: true,
+            help_context: None,
         }
     }
 
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
     pub fn selected_item(&self) -> Option<&str> {
         self.items.get(self.selected_index).map(String::as_str)
     }
@@ -43,18 +49,14 @@ impl TView for TRadioButtons {
         }
     }
 
-    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+    fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
         if let TEvent::Key(KeyEvent { code, .. }) = event {
             match code {
-                KeyCode::Up => {
-                    if self.focus_index > 0 {
-                        self.focus_index -= 1;
-                    }
+                KeyCode::Up if self.focus_index > 0 => {
+                    self.focus_index -= 1;
                 }
-                KeyCode::Down => {
-                    if self.focus_index + 1 < self.items.len() {
-                        self.focus_index += 1;
-                    }
+                KeyCode::Down if self.focus_index + 1 < self.items.len() => {
+                    self.focus_index += 1;
                 }
                 KeyCode::Enter | KeyCode::Char(' ') => {
                     self.selected_index = self.focus_index;
@@ -78,4 +80,21 @@ impl TView for TRadioButtons {
     fn is_focusable(&self) -> bool {
         true
     }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    fn get_data(&self) -> Option<TData> {
+        Some(TData::Index(self.selected_index))
+    }
+
+    fn set_data(&mut self, data: TData) {
+        if let TData::Index(index) = data {
+            if index < self.items.len() {
+                self.selected_index = index;
+                self.focus_index = index;
+            }
+        }
+    }
 }
@@ -0,0 +1,416 @@
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::input::WHEEL_STEP;
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+
+const SCROLLBAR_FG: Color = Color::Cyan;
+const SCROLLBAR_BG: Color = Color::Blue;
+
+/// Which way a scroll bar runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
+/// Callback run with the new value when the user moves a scroll bar
+pub type ScrollCallback = Box<dyn FnMut(usize, &TEventQueue)>;
+
+/// A scroll bar: arrows at both ends and a track with a thumb showing
+/// `value` between 0 and `max`, running the way `orientation` says.
+/// Clicking an arrow moves by `arrow_step`, clicking the track beside the
+/// thumb by `page_step`, and the thumb can be dragged.
+///
+/// A scrollable view links a bar by owning it: it hands the bar its mouse
+/// events, takes the value `scroll` reports as changed, and calls
+/// `set_params` whenever it scrolls by itself. A bar never takes the
+/// focus; its keys reach it through the owning view.
+pub struct TScrollBar {
+    pub bounds: TRect,
+    pub orientation: Orientation,
+    pub value: usize,
+    pub max: usize,
+    pub page_step: usize,
+    pub arrow_step: usize,
+    pub focused: bool,
+    pub help_context: Option<u16>,
+    pub on_change: Option<ScrollCallback>,
+    colors: (Color, Color),
+    dragging: bool,
+}
+
+impl TScrollBar {
+    pub fn new(bounds: TRect, orientation: Orientation) -> Self {
+        Self {
+            bounds,
+            orientation,
+            value: 0,
+            max: 0,
+            page_step: 1,
+            arrow_step: 1,
+            focused: false,
+            help_context: None,
+            on_change: None,
+            colors: (SCROLLBAR_FG, SCROLLBAR_BG),
+            dragging: false,
+        }
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    pub fn set_colors(&mut self, fg: Color, bg: Color) {
+        self.colors = (fg, bg);
+    }
+
+    /// Sets a callback run when the user changes the value
+    pub fn set_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(usize, &TEventQueue) + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+
+    /// Sets the range and position in one go; `value` is kept within `max`
+    pub fn set_params(&mut self, value: usize, max: usize, page_step: usize) {
+        self.max = max;
+        self.page_step = page_step.max(1);
+        self.value = value.min(max);
+    }
+
+    pub fn set_value(&mut self, value: usize) {
+        self.value = value.min(self.max);
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.orientation == Orientation::Vertical
+    }
+
+    /// Whether `mouse` is for this bar: it is over the bar, or the thumb is
+    /// being dragged
+    pub fn handles_mouse(&self, mouse: &MouseEvent) -> bool {
+        match mouse.kind {
+            MouseEventKind::Drag(_) | MouseEventKind::Up(_) => self.dragging,
+            _ => self.bounds.contains(TPoint { x: mouse.column, y: mouse.row }),
+        }
+    }
+
+    /// Handles `event` and returns the new value if it moved the bar. This
+    /// is how an owning view learns that it should scroll.
+    pub fn scroll(&mut self, event: &TEvent) -> Option<usize> {
+        let value = match event {
+            TEvent::Key(key) => self.key_value(key)?,
+            TEvent::Mouse(mouse) => self.mouse_value(mouse)?,
+            _ => return None,
+        }
+        .min(self.max);
+        if value == self.value {
+            return None;
+        }
+        self.value = value;
+        Some(value)
+    }
+
+    fn length(&self) -> usize {
+        let length = if self.is_vertical() { self.bounds.height } else { self.bounds.width };
+        length as usize
+    }
+
+    /// Cells between the two arrows
+    fn track(&self) -> usize {
+        self.length().saturating_sub(2)
+    }
+
+    fn back(&self, step: usize) -> usize {
+        self.value.saturating_sub(step)
+    }
+
+    fn forward(&self, step: usize) -> usize {
+        self.value + step
+    }
+
+    fn key_value(&self, key: &KeyEvent) -> Option<usize> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let vertical = self.is_vertical();
+        let value = match key.code {
+            KeyCode::Up if vertical => self.back(self.arrow_step),
+            KeyCode::Down if vertical => self.forward(self.arrow_step),
+            KeyCode::PageUp if vertical && ctrl => 0,
+            KeyCode::PageDown if vertical && ctrl => self.max,
+            KeyCode::PageUp if vertical => self.back(self.page_step),
+            KeyCode::PageDown if vertical => self.forward(self.page_step),
+            KeyCode::Left if !vertical && ctrl => self.back(self.page_step),
+            KeyCode::Right if !vertical && ctrl => self.forward(self.page_step),
+            KeyCode::Left if !vertical => self.back(self.arrow_step),
+            KeyCode::Right if !vertical => self.forward(self.arrow_step),
+            KeyCode::Home => 0,
+            KeyCode::End => self.max,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn mouse_value(&mut self, mouse: &MouseEvent) -> Option<usize> {
+        let pos = if self.is_vertical() {
+            mouse.row.checked_sub(self.bounds.y)
+        } else {
+            mouse.column.checked_sub(self.bounds.x)
+        };
+        let inside = self.bounds.contains(TPoint { x: mouse.column, y: mouse.row });
+        let step = self.arrow_step * WHEEL_STEP;
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if inside => {
+                let pos = pos? as usize;
+                let thumb = 1 + thumb_pos(self.value, self.max, self.track());
+                match pos {
+                    0 => Some(self.back(self.arrow_step)),
+                    p if p + 1 == self.length() => Some(self.forward(self.arrow_step)),
+                    p if p < thumb => Some(self.back(self.page_step)),
+                    p if p > thumb => Some(self.forward(self.page_step)),
+                    _ => {
+                        self.dragging = true;
+                        None
+                    }
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging => {
+                // Dragging past either end keeps the thumb at that end
+                let pos = pos.unwrap_or(0) as usize;
+                let track = self.track();
+                if track < 2 {
+                    return None;
+                }
+                let cell = pos.clamp(1, track) - 1;
+                Some((cell * self.max + (track - 1) / 2) / (track - 1))
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.dragging = false;
+                None
+            }
+            MouseEventKind::ScrollUp if inside && self.is_vertical() => Some(self.back(step)),
+            MouseEventKind::ScrollDown if inside && self.is_vertical() => Some(self.forward(step)),
+            MouseEventKind::ScrollLeft if inside && !self.is_vertical() => Some(self.back(step)),
+            MouseEventKind::ScrollRight if inside && !self.is_vertical() => Some(self.forward(step)),
+            _ => None,
+        }
+    }
+}
+
+/// The scroll bars of a scrollable view: an optional vertical one right of
+/// its content area and an optional horizontal one below it. Positions,
+/// ranges and pages are (column, line) pairs.
+#[derive(Default)]
+pub(crate) struct ScrollBars {
+    vertical: Option<TScrollBar>,
+    horizontal: Option<TScrollBar>,
+}
+
+impl ScrollBars {
+    /// Shows or hides each bar; `layout` places the ones shown
+    pub fn show(&mut self, vertical: bool, horizontal: bool) {
+        self.vertical = vertical.then(|| TScrollBar::new(TRect::new(0, 0, 0, 0), Orientation::Vertical));
+        self.horizontal = horizontal.then(|| TScrollBar::new(TRect::new(0, 0, 0, 0), Orientation::Horizontal));
+    }
+
+    /// Part of `bounds` left for the content beside the bars shown
+    pub fn content_area(&self, bounds: TRect) -> TRect {
+        let bar_width = u16::from(self.vertical.is_some());
+        let bar_height = u16::from(self.horizontal.is_some());
+        TRect::new(
+            bounds.x,
+            bounds.y,
+            bounds.width.saturating_sub(bar_width),
+            bounds.height.saturating_sub(bar_height),
+        )
+    }
+
+    /// Puts the bars beside the content `area`
+    pub fn layout(&mut self, area: TRect) {
+        if let Some(bar) = &mut self.vertical {
+            bar.set_bounds(TRect::new(area.x + area.width, area.y, 1, area.height));
+        }
+        if let Some(bar) = &mut self.horizontal {
+            bar.set_bounds(TRect::new(area.x, area.y + area.height, area.width, 1));
+        }
+    }
+
+    /// Moves the bars to `position`, out of `max`
+    pub fn sync(&mut self, position: (usize, usize), max: (usize, usize), page: (usize, usize)) {
+        if let Some(bar) = &mut self.vertical {
+            bar.set_params(position.1, max.1, page.1);
+        }
+        if let Some(bar) = &mut self.horizontal {
+            bar.set_params(position.0, max.0, page.0);
+        }
+    }
+
+    /// Hands `mouse` to the bar it is for and returns the scroll position,
+    /// `position` moved as the bar was, or `None` if it is for neither bar
+    pub fn handle_mouse(&mut self, mouse: &MouseEvent, position: (usize, usize)) -> Option<(usize, usize)> {
+        let (mut x, mut y) = position;
+        if let Some(bar) = self.vertical.as_mut().filter(|bar| bar.handles_mouse(mouse)) {
+            y = bar.scroll(&TEvent::Mouse(*mouse)).unwrap_or(y);
+        } else if let Some(bar) = self.horizontal.as_mut().filter(|bar| bar.handles_mouse(mouse)) {
+            x = bar.scroll(&TEvent::Mouse(*mouse)).unwrap_or(x);
+        } else {
+            return None;
+        }
+        Some((x, y))
+    }
+
+    pub fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        for bar in self.vertical.iter().chain(&self.horizontal) {
+            bar.draw(buffer, offset);
+        }
+    }
+}
+
+/// Thumb offset within a scroll bar track of `track` cells
+fn thumb_pos(value: usize, max: usize, track: usize) -> usize {
+    if max == 0 || track == 0 {
+        0
+    } else {
+        (value.min(max) * (track - 1) + max / 2) / max
+    }
+}
+
+impl TView for TScrollBar {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let length = self.length() as u16;
+        if length < 2 {
+            return;
+        }
+        let (fg, bg) = self.colors;
+        let (back, forward) = if self.is_vertical() { ('▲', '▼') } else { ('◄', '►') };
+        let thumb = 1 + thumb_pos(self.value, self.max, self.track()) as u16;
+        let cell = |i: u16| {
+            if self.is_vertical() {
+                (offset.0 + self.bounds.x, offset.1 + self.bounds.y + i)
+            } else {
+                (offset.0 + self.bounds.x + i, offset.1 + self.bounds.y)
+            }
+        };
+
+        for i in 0..length {
+            let ch = match i {
+                0 => back,
+                i if i + 1 == length => forward,
+                i if i == thumb => '■',
+                _ => '░',
+            };
+            let (x, y) = cell(i);
+            buffer.set_styled(x, y, ch, fg, bg);
+        }
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        if let Some(value) = self.scroll(&event) {
+            if let Some(callback) = &mut self.on_change {
+                callback(value, queue);
+            }
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.dragging = false;
+        }
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> TEvent {
+        TEvent::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })
+    }
+
+    fn down(column: u16, row: u16) -> TEvent {
+        mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+    }
+
+    /// Vertical bar in rows 2 to 13 of column 5: arrows in rows 2 and 13,
+    /// a track of 10 cells between, at 50 of 100
+    fn bar() -> TScrollBar {
+        let mut bar = TScrollBar::new(TRect::new(5, 2, 1, 12), Orientation::Vertical);
+        bar.set_params(50, 100, 20);
+        bar
+    }
+
+    #[test]
+    fn thumb_reaches_both_ends_of_track() {
+        assert_eq!(thumb_pos(0, 100, 10), 0);
+        assert_eq!(thumb_pos(50, 100, 10), 5);
+        assert_eq!(thumb_pos(100, 100, 10), 9);
+        assert_eq!(thumb_pos(150, 100, 10), 9);
+        assert_eq!(thumb_pos(1, 1, 10), 9);
+        assert_eq!(thumb_pos(5, 0, 10), 0);
+        assert_eq!(thumb_pos(5, 100, 0), 0);
+    }
+
+    #[test]
+    fn clicks_beside_thumb_move_by_page() {
+        let mut bar = bar();
+        // The thumb is in row 8
+        assert_eq!(bar.scroll(&down(5, 8)), None);
+        bar.scroll(&mouse(MouseEventKind::Up(MouseButton::Left), 5, 8));
+
+        assert_eq!(bar.scroll(&down(5, 4)), Some(30));
+        // Now the thumb is in row 6
+        assert_eq!(bar.scroll(&down(5, 12)), Some(50));
+        assert_eq!(bar.scroll(&down(5, 7)), Some(30));
+
+        // Arrows move by one, and the bar stops at either end
+        assert_eq!(bar.scroll(&down(5, 2)), Some(29));
+        assert_eq!(bar.scroll(&down(5, 13)), Some(30));
+        bar.set_value(90);
+        assert_eq!(bar.scroll(&down(5, 12)), Some(100));
+        bar.set_value(10);
+        assert_eq!(bar.scroll(&down(5, 2)), Some(9));
+        assert_eq!(bar.scroll(&down(5, 3)), Some(0));
+    }
+
+    #[test]
+    fn horizontal_clicks_use_columns() {
+        let mut bar = TScrollBar::new(TRect::new(0, 5, 12, 1), Orientation::Horizontal);
+        bar.set_params(0, 100, 20);
+        assert_eq!(bar.scroll(&down(6, 5)), Some(20));
+        assert_eq!(bar.scroll(&down(1, 5)), Some(0));
+    }
+
+    #[test]
+    fn drag_past_either_end_stops_at_it() {
+        let mut bar = bar();
+        assert_eq!(bar.scroll(&down(5, 8)), None);
+        let drag = |column, row| mouse(MouseEventKind::Drag(MouseButton::Left), column, row);
+
+        assert!(bar.handles_mouse(&MouseEvent { kind: MouseEventKind::Drag(MouseButton::Left), column: 40, row: 0, modifiers: KeyModifiers::NONE }));
+        assert_eq!(bar.scroll(&drag(40, 0)), Some(0));
+        assert_eq!(bar.scroll(&drag(5, 2)), None);
+        assert_eq!(bar.scroll(&drag(5, 30)), Some(100));
+        assert_eq!(bar.scroll(&drag(5, 13)), None);
+        assert_eq!(bar.scroll(&drag(5, 7)), Some(44));
+
+        // Once released, drags are no longer the bar's
+        bar.scroll(&mouse(MouseEventKind::Up(MouseButton::Left), 5, 7));
+        assert_eq!(bar.scroll(&drag(5, 30)), None);
+    }
+}
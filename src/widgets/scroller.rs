@@ -0,0 +1,232 @@
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::input::WHEEL_STEP;
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::scrollbar::ScrollBars;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+
+const SCROLLER_FG: Color = Color::White;
+const SCROLLER_BG: Color = Color::Blue;
+
+/// Draws the visible part of a scroller's content: a buffer holding just
+/// the content area, that area within it and the content position shown at
+/// its top-left cell
+pub type ScrollerDrawCallback = Box<dyn Fn(&mut ScreenBuffer, TRect, (usize, usize))>;
+
+/// A view onto content larger than itself. The content is `limit` cells
+/// wide and high and is never stored: the draw callback is asked for just
+/// the cells on screen, starting at `delta`, and cannot draw outside them.
+/// Scroll bars, when shown, take the right column and the bottom row.
+pub struct TScroller {
+    pub bounds: TRect,
+    pub focused: bool,
+    pub help_context: Option<u16>,
+    pub limit: (usize, usize),     // Content width and height
+    pub delta: (usize, usize),     // Content column and line shown top-left
+    draw_content: Option<ScrollerDrawCallback>,
+    scroll_bars: ScrollBars,
+    colors: (Color, Color),
+}
+
+impl TScroller {
+    pub fn new(bounds: TRect) -> Self {
+        Self {
+            bounds,
+            focused: true,
+            help_context: None,
+            limit: (0, 0),
+            delta: (0, 0),
+            draw_content: None,
+            scroll_bars: ScrollBars::default(),
+            colors: (SCROLLER_FG, SCROLLER_BG),
+        }
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    /// Sets the colors the content area is cleared to before drawing
+    pub fn set_colors(&mut self, fg: Color, bg: Color) {
+        self.colors = (fg, bg);
+    }
+
+    /// Sets the callback that draws the visible content
+    pub fn set_draw_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut ScreenBuffer, TRect, (usize, usize)) + 'static,
+    {
+        self.draw_content = Some(Box::new(callback));
+    }
+
+    /// Shows or hides the vertical and horizontal scroll bars
+    pub fn set_scroll_bars(&mut self, vertical: bool, horizontal: bool) {
+        self.scroll_bars.show(vertical, horizontal);
+        self.layout_scroll_bars();
+    }
+
+    /// Sets the content size, keeping `delta` within it
+    pub fn set_limit(&mut self, width: usize, height: usize) {
+        self.limit = (width, height);
+        self.scroll_to(self.delta.0, self.delta.1);
+    }
+
+    /// Shows the content from column `x` and line `y`, as far as the
+    /// content size allows
+    pub fn scroll_to(&mut self, x: usize, y: usize) {
+        let (max_x, max_y) = self.max_delta();
+        self.delta = (x.min(max_x), y.min(max_y));
+        self.sync_scroll_bars();
+    }
+
+    /// Area the content is drawn in, in the same coordinates as `bounds`
+    fn content_bounds(&self) -> TRect {
+        self.scroll_bars.content_area(self.bounds)
+    }
+
+    fn max_delta(&self) -> (usize, usize) {
+        let area = self.content_bounds();
+        (
+            self.limit.0.saturating_sub(area.width as usize),
+            self.limit.1.saturating_sub(area.height as usize),
+        )
+    }
+
+    fn layout_scroll_bars(&mut self) {
+        self.scroll_bars.layout(self.content_bounds());
+        self.sync_scroll_bars();
+    }
+
+    /// Moves the scroll bars to `delta`
+    fn sync_scroll_bars(&mut self) {
+        let area = self.content_bounds();
+        let page = (area.width as usize, area.height as usize);
+        self.scroll_bars.sync(self.delta, self.max_delta(), page);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let area = self.content_bounds();
+        let (x, y) = self.delta;
+        let (page_x, page_y) = (area.width.max(1) as usize, area.height.max(1) as usize);
+        let (max_x, max_y) = self.max_delta();
+        let (x, y) = match key.code {
+            KeyCode::Up => (x, y.saturating_sub(1)),
+            KeyCode::Down => (x, y + 1),
+            KeyCode::Left if ctrl => (x.saturating_sub(page_x), y),
+            KeyCode::Right if ctrl => (x + page_x, y),
+            KeyCode::Left => (x.saturating_sub(1), y),
+            KeyCode::Right => (x + 1, y),
+            KeyCode::PageUp => (x, y.saturating_sub(page_y)),
+            KeyCode::PageDown => (x, y + page_y),
+            KeyCode::Home if ctrl => (0, 0),
+            KeyCode::End if ctrl => (max_x, max_y),
+            KeyCode::Home => (0, y),
+            KeyCode::End => (max_x, y),
+            _ => return,
+        };
+        self.scroll_to(x, y);
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if let Some(delta) = self.scroll_bars.handle_mouse(&mouse, self.delta) {
+            self.delta = delta;
+            return;
+        }
+        if !self.bounds.contains(TPoint { x: mouse.column, y: mouse.row }) {
+            return;
+        }
+        let (x, y) = self.delta;
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll_to(x, y.saturating_sub(WHEEL_STEP)),
+            MouseEventKind::ScrollDown => self.scroll_to(x, y + WHEEL_STEP),
+            MouseEventKind::ScrollLeft => self.scroll_to(x.saturating_sub(WHEEL_STEP), y),
+            MouseEventKind::ScrollRight => self.scroll_to(x + WHEEL_STEP, y),
+            _ => {}
+        }
+    }
+}
+
+impl TView for TScroller {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let area = self.content_bounds();
+        let (fg, bg) = self.colors;
+        let whole = TRect::new(offset.0 + self.bounds.x, offset.1 + self.bounds.y, self.bounds.width, self.bounds.height);
+        buffer.fill(whole, ' ', fg, bg);
+        if let Some(draw_content) = &self.draw_content {
+            if area.width > 0 && area.height > 0 {
+                // The callback draws into a buffer of its own, so nothing it
+                // writes can land outside the content area
+                let screen = TRect::new(0, 0, area.width, area.height);
+                let mut content = ScreenBuffer::new(area.width, area.height);
+                content.fill(screen, ' ', fg, bg);
+                draw_content(&mut content, screen, self.delta);
+                buffer.blit(offset.0 + area.x, offset.1 + area.y, &content);
+            }
+        }
+
+        self.scroll_bars.draw(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, _queue: &TEventQueue) {
+        match event {
+            TEvent::Key(key) => self.handle_key(key),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+        self.layout_scroll_bars();
+        self.scroll_to(self.delta.0, self.delta.1);
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctrl_end_shows_the_bottom_right_corner() {
+        let mut scroller = TScroller::new(TRect::new(0, 0, 10, 5));
+        scroller.set_limit(30, 20);
+        scroller.handle_event(TEvent::Key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL)), &TEventQueue::new());
+        assert_eq!(scroller.delta, (20, 15));
+    }
+
+    #[test]
+    fn content_is_clipped_to_its_area() {
+        let mut scroller = TScroller::new(TRect::new(2, 1, 4, 3));
+        scroller.set_scroll_bars(true, false);
+        scroller.set_draw_callback(|buffer, _area, _delta| {
+            buffer.fill(TRect::new(0, 0, 20, 20), 'x', Color::Red, Color::Black);
+        });
+        let mut buffer = ScreenBuffer::new(10, 6);
+        scroller.draw(&mut buffer, (0, 0));
+        for y in 0..6 {
+            for x in 0..10 {
+                let inside = (2..5).contains(&x) && (1..4).contains(&y);
+                assert_eq!(buffer.get(x, y).unwrap().ch == 'x', inside, "cell {x},{y}");
+            }
+        }
+    }
+}
@@ -1,11 +1,86 @@
-use crate::core::rect::TRect;
+use crate::core::command::TCommandSet;
+use crate::core::rect::{TPoint, TRect};
 use crate::core::event::{TEvent, TEventQueue};
 use crate::core::view::TView;
 use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::Color;
+
+const STATUS_FG: Color = Color::Black;
+const STATUS_BG: Color = Color::Grey;
+const HOTKEY_FG: Color = Color::Red;
+
+/// Maps a help context to a one-line hint
+pub type HintProvider = Box<dyn Fn(u16) -> Option<String>>;
+
+/// A status line entry that is both displayed and bound to a key
+#[derive(Debug, Clone)]
+pub struct TStatusItem {
+    pub label: String,              // Label text with ~key~ markers, e.g. "~Alt-X~ Exit"
+    pub key: KeyCode,               // Key that fires the command
+    pub modifiers: KeyModifiers,    // Modifiers that must be held with the key
+    pub command: u16,               // Command posted on key or click
+}
+
+impl TStatusItem {
+    pub fn new(label: &str, key: KeyCode, command: u16) -> Self {
+        Self {
+            label: label.to_string(),
+            key,
+            modifiers: KeyModifiers::NONE,
+            command,
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: KeyModifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Returns true if `key` is this item's key binding
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        match (self.key, key.code) {
+            // Letters arrive upper-cased with SHIFT when Shift or Caps Lock is held
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                a.eq_ignore_ascii_case(&b)
+                    && key.modifiers.difference(KeyModifiers::SHIFT) == self.modifiers.difference(KeyModifiers::SHIFT)
+            }
+            (a, b) => a == b && key.modifiers == self.modifiers,
+        }
+    }
+
+    /// Display width of the label without `~` markers
+    fn width(&self) -> u16 {
+        self.label.chars().filter(|&c| c != '~').count() as u16
+    }
+}
+
+/// A set of status items shown while the focused view's help context lies
+/// in `min..=max`
+#[derive(Debug, Clone)]
+pub struct TStatusDef {
+    pub min: u16,
+    pub max: u16,
+    pub items: Vec<TStatusItem>,
+}
+
+impl TStatusDef {
+    pub fn new(min: u16, max: u16, items: Vec<TStatusItem>) -> Self {
+        Self { min, max, items }
+    }
+
+    fn contains(&self, ctx: u16) -> bool {
+        ctx >= self.min && ctx <= self.max
+    }
+}
 
 pub struct TStatusLine {
     pub text: String,
     pub bounds: TRect,
+    pub defs: Vec<TStatusDef>,
+    help_context: Option<u16>,
+    commands: TCommandSet,
+    hint_provider: Option<HintProvider>,
 }
 
 impl TStatusLine {
@@ -13,31 +88,131 @@ impl TStatusLine {
         Self {
             text: text.to_string(),
             bounds,
+            defs: Vec::new(),
+            help_context: None,
+            commands: TCommandSet::new(),
+            hint_provider: None,
         }
     }
 
     pub fn set_text(&mut self, text: &str) {
         self.text = text.to_string();
     }
+
+    /// Adds an item set. Sets are searched in the order they were added.
+    pub fn add_def(&mut self, def: TStatusDef) {
+        self.defs.push(def);
+    }
+
+    /// Shares the command set used to hide items with disabled commands
+    pub fn set_command_set(&mut self, commands: TCommandSet) {
+        self.commands = commands;
+    }
+
+    /// Selects the item set for the focused view's help context.
+    /// Views without a context select the sets covering context 0.
+    pub fn set_help_context(&mut self, ctx: Option<u16>) {
+        self.help_context = ctx;
+    }
+
+    /// Sets the hook that describes help contexts. While the selected menu
+    /// item or focused control has a context with a hint, the hint is shown
+    /// after the items in place of `text`.
+    pub fn set_hint_provider<F>(&mut self, provider: F)
+    where
+        F: Fn(u16) -> Option<String> + 'static,
+    {
+        self.hint_provider = Some(Box::new(provider));
+    }
+
+    /// Returns the hint for the current help context, if any
+    pub fn hint(&self) -> Option<String> {
+        let ctx = self.help_context?;
+        self.hint_provider.as_ref().and_then(|provider| provider(ctx))
+    }
+
+    /// Returns the enabled items of the set matching the current help context
+    pub fn visible_items(&self) -> impl Iterator<Item = &TStatusItem> {
+        let ctx = self.help_context.unwrap_or(0);
+        self.defs
+            .iter()
+            .find(|def| def.contains(ctx))
+            .into_iter()
+            .flat_map(|def| def.items.iter())
+            .filter(|item| self.commands.is_enabled(item.command))
+    }
+
+    /// Returns the command bound to `key`, if a visible item has that key
+    pub fn command_for_key(&self, key: &KeyEvent) -> Option<u16> {
+        self.visible_items().find(|item| item.matches(key)).map(|item| item.command)
+    }
+
+    /// Returns the command of the visible item at screen column `x`
+    pub fn command_at(&self, x: u16) -> Option<u16> {
+        let mut item_x = self.bounds.x + 1;
+        for item in self.visible_items() {
+            let width = item.width();
+            if x >= item_x && x < item_x + width {
+                return Some(item.command);
+            }
+            item_x += width + 2;
+        }
+        None
+    }
 }
 
 impl TView for TStatusLine {
     fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
-        let x = offset.0 + self.bounds.x;
+        let x0 = offset.0 + self.bounds.x;
         let y = offset.1 + self.bounds.y;
-        let width = self.bounds.width as usize;
+        let right = x0 + self.bounds.width;
 
-        let line = if self.text.len() > width {
-            self.text[..width].to_string()
-        } else {
-            format!("{:<width$}", self.text, width = width)
-        };
+        buffer.fill(TRect::new(x0, y, self.bounds.width, 1), ' ', STATUS_FG, STATUS_BG);
 
-        buffer.write_str(x, y, &line);
+        let mut x = x0 + 1;
+        for item in self.visible_items() {
+            let mut in_key = false;
+            for ch in item.label.chars() {
+                if ch == '~' {
+                    in_key = !in_key;
+                    continue;
+                }
+                if x < right {
+                    let fg = if in_key { HOTKEY_FG } else { STATUS_FG };
+                    buffer.set_styled(x, y, ch, fg, STATUS_BG);
+                }
+                x += 1;
+            }
+            x += 2;
+        }
+
+        let hint = self.hint().map(|hint| format!("│ {}", hint));
+        for ch in hint.as_deref().unwrap_or(&self.text).chars() {
+            if x >= right {
+                break;
+            }
+            buffer.set_styled(x, y, ch, STATUS_FG, STATUS_BG);
+            x += 1;
+        }
     }
 
-    fn handle_event(&mut self, _event: TEvent, queue: &TEventQueue) {
-        // passive; no events
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        let command = match event {
+            TEvent::Key(key) => self.command_for_key(&key),
+            TEvent::Mouse(mouse) if mouse.kind == MouseEventKind::Up(MouseButton::Left) => {
+                let pt = TPoint { x: mouse.column, y: mouse.row };
+                if self.bounds.contains(pt) {
+                    self.command_at(pt.x)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(command) = command {
+            queue.put_event(TEvent::Command(command));
+        }
     }
 
     fn get_bounds(&self) -> TRect {
@@ -57,3 +232,122 @@ impl TView for TStatusLine {
 
     // set_focus not overridden — default no-op
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::MouseEvent;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    /// Editor items for contexts 100 to 199, exit items for all others
+    fn status_line() -> TStatusLine {
+        let mut line = TStatusLine::new("Ready", TRect::new(0, 9, 40, 1));
+        line.add_def(TStatusDef::new(100, 199, vec![
+            TStatusItem::new("~F2~ Save", KeyCode::F(2), 20),
+            TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), 21).with_modifiers(KeyModifiers::ALT),
+        ]));
+        line.add_def(TStatusDef::new(0, u16::MAX, vec![TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), 21).with_modifiers(KeyModifiers::ALT)]));
+        line
+    }
+
+    fn commands(line: &TStatusLine) -> Vec<u16> {
+        line.visible_items().map(|item| item.command).collect()
+    }
+
+    #[test]
+    fn items_match_their_keys() {
+        let item = TStatusItem::new("~Alt-X~ Exit", KeyCode::Char('x'), 21).with_modifiers(KeyModifiers::ALT);
+        assert!(item.matches(&key(KeyCode::Char('x'), KeyModifiers::ALT)));
+        // Shift or Caps Lock upper-cases the letter and may add SHIFT
+        assert!(item.matches(&key(KeyCode::Char('X'), KeyModifiers::ALT | KeyModifiers::SHIFT)));
+        assert!(item.matches(&key(KeyCode::Char('X'), KeyModifiers::ALT)));
+        assert!(!item.matches(&key(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(!item.matches(&key(KeyCode::Char('x'), KeyModifiers::ALT | KeyModifiers::CONTROL)));
+
+        // Other keys must match their modifiers exactly
+        let item = TStatusItem::new("~F2~ Save", KeyCode::F(2), 20);
+        assert!(item.matches(&key(KeyCode::F(2), KeyModifiers::NONE)));
+        assert!(!item.matches(&key(KeyCode::F(2), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn help_context_selects_items() {
+        let mut line = status_line();
+        assert_eq!(commands(&line), vec![21]);
+        line.set_help_context(Some(150));
+        assert_eq!(commands(&line), vec![20, 21]);
+        assert_eq!(line.command_for_key(&key(KeyCode::F(2), KeyModifiers::NONE)), Some(20));
+        line.set_help_context(Some(200));
+        assert_eq!(commands(&line), vec![21]);
+        assert_eq!(line.command_for_key(&key(KeyCode::F(2), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn disabled_commands_are_hidden() {
+        let mut line = status_line();
+        let commands_set = TCommandSet::new();
+        line.set_command_set(commands_set.clone());
+        line.set_help_context(Some(100));
+        commands_set.disable_command(20);
+        assert_eq!(commands(&line), vec![21]);
+        assert_eq!(line.command_for_key(&key(KeyCode::F(2), KeyModifiers::NONE)), None);
+        // Later items move left into the gap
+        assert_eq!(line.command_at(1), Some(21));
+    }
+
+    #[test]
+    fn clicks_map_to_items() {
+        let queue = TEventQueue::new();
+        let mut line = status_line();
+        line.set_help_context(Some(100));
+        // " F2 Save  Alt-X Exit": items at columns 1..8 and 10..20
+        assert_eq!(line.command_at(0), None);
+        assert_eq!(line.command_at(1), Some(20));
+        assert_eq!(line.command_at(7), Some(20));
+        assert_eq!(line.command_at(8), None);
+        assert_eq!(line.command_at(10), Some(21));
+        assert_eq!(line.command_at(19), Some(21));
+        assert_eq!(line.command_at(20), None);
+
+        let click = |column, row| {
+            TEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        line.handle_event(click(12, 9), &queue);
+        assert!(matches!(queue.get_event(), Some(TEvent::Command(21))));
+        // Clicks off the line are ignored
+        line.handle_event(click(12, 8), &queue);
+        assert!(queue.get_event().is_none());
+    }
+
+    fn row_text(buffer: &ScreenBuffer, y: u16) -> String {
+        (0..buffer.width()).filter_map(|x| buffer.get(x, y)).map(|cell| cell.ch).collect::<String>().trim_end().to_string()
+    }
+
+    #[test]
+    fn hint_replaces_text() {
+        let mut line = status_line();
+        line.set_hint_provider(|ctx| (ctx == 150).then(|| "Saves the file".to_string()));
+        let mut buffer = ScreenBuffer::new(40, 10);
+        line.draw(&mut buffer, (0, 0));
+        assert_eq!(row_text(&buffer, 9), " Alt-X Exit  Ready");
+
+        line.set_help_context(Some(150));
+        assert_eq!(line.hint().as_deref(), Some("Saves the file"));
+        line.draw(&mut buffer, (0, 0));
+        assert_eq!(row_text(&buffer, 9), " F2 Save  Alt-X Exit  │ Saves the file");
+
+        // Contexts without a hint show the text again
+        line.set_help_context(Some(120));
+        assert_eq!(line.hint(), None);
+        line.draw(&mut buffer, (0, 0));
+        assert_eq!(row_text(&buffer, 9), " F2 Save  Alt-X Exit  Ready");
+    }
+}
@@ -0,0 +1,649 @@
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::input::{DOUBLE_CLICK_TIME, WHEEL_STEP};
+use crate::core::view::{TData, TView};
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::scrollbar::ScrollBars;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+use std::cmp::Ordering;
+use std::time::Instant;
+
+const TABLE_FG: Color = Color::White;
+const TABLE_BG: Color = Color::Blue;
+const HEADER_FG: Color = Color::Yellow;
+const HEADER_BG: Color = Color::Blue;
+const SELECTED_FG: Color = Color::Black;
+const SELECTED_BG: Color = Color::Cyan;
+
+/// Rows shown by a `TTable`. Only the visible cells are asked for, except
+/// when sorting, which compares every row of the sort column.
+pub trait TableDataSource {
+    fn row_count(&self) -> usize;
+
+    fn cell_text(&self, row: usize, column: usize) -> String;
+
+    /// Orders rows `a` and `b` by `column`; compares the cell text unless
+    /// overridden, e.g. for numbers or dates
+    fn compare(&self, a: usize, b: usize, column: usize) -> Ordering {
+        self.cell_text(a, column).cmp(&self.cell_text(b, column))
+    }
+}
+
+impl TableDataSource for Vec<Vec<String>> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+
+    fn cell_text(&self, row: usize, column: usize) -> String {
+        self.get(row).and_then(|cells| cells.get(column)).cloned().unwrap_or_default()
+    }
+}
+
+/// Where a cell's text sits within its column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// One column of a `TTable`
+#[derive(Debug, Clone)]
+pub struct TColumn {
+    pub title: String,
+    pub width: u16,
+    pub align: Alignment,
+    pub min_width: u16,
+    pub max_width: u16,
+    pub flexible: bool,     // Grows to share space the fixed columns leave
+}
+
+impl TColumn {
+    pub fn new(title: &str, width: u16) -> Self {
+        Self {
+            title: title.to_string(),
+            width: width.max(1),
+            align: Alignment::Left,
+            min_width: 1,
+            max_width: u16::MAX,
+            flexible: false,
+        }
+    }
+
+    pub fn with_align(mut self, align: Alignment) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Limits the width the column can be dragged or grown to
+    pub fn with_limits(mut self, min_width: u16, max_width: u16) -> Self {
+        self.min_width = min_width.max(1);
+        self.max_width = max_width.max(self.min_width);
+        self.width = self.width.clamp(self.min_width, self.max_width);
+        self
+    }
+
+    pub fn with_flexible(mut self, flexible: bool) -> Self {
+        self.flexible = flexible;
+        self
+    }
+}
+
+/// Pads or cuts `text` to exactly `width` chars
+fn align_text(text: &str, width: usize, align: Alignment) -> String {
+    let text: String = text.chars().take(width).collect();
+    match align {
+        Alignment::Left => format!("{:<width$}", text, width = width),
+        Alignment::Center => format!("{:^width$}", text, width = width),
+        Alignment::Right => format!("{:>width$}", text, width = width),
+    }
+}
+
+/// A table of rows under a header row of column titles. Clicking a title,
+/// or Alt with the column's number, sorts by that column and a second time
+/// reverses the order. Dragging the separator after a title resizes the
+/// column within its limits and makes it fixed. The rows come lazily from
+/// a `TableDataSource`; tables wider than the view scroll sideways.
+/// Changes to the selected row post `ListSelectionChanged` and Enter or a
+/// double-click posts `ListItemActivated`, both with the source row index.
+pub struct TTable {
+    pub bounds: TRect,
+    pub columns: Vec<TColumn>,
+    pub focused: bool,
+    pub help_context: Option<u16>,
+    pub table_id: u16,              // Sent with the table's events to tell tables apart
+    source: Box<dyn TableDataSource>,
+    order: Option<Vec<usize>>,      // Source rows in display order when sorted
+    sort: Option<(usize, bool)>,    // Sort column and whether ascending
+    selected: usize,                // Selected display row
+    scroll_row: usize,              // First visible display row
+    scroll_x: usize,                // First visible content column
+    resizing: Option<usize>,        // Column whose separator is being dragged
+    last_click: Option<(Instant, usize)>,
+    scroll_bars: ScrollBars,
+}
+
+impl TTable {
+    pub fn new(bounds: TRect, columns: Vec<TColumn>, source: Box<dyn TableDataSource>) -> Self {
+        Self {
+            bounds,
+            columns,
+            focused: true,
+            help_context: None,
+            table_id: 0,
+            source,
+            order: None,
+            sort: None,
+            selected: 0,
+            scroll_row: 0,
+            scroll_x: 0,
+            resizing: None,
+            last_click: None,
+            scroll_bars: ScrollBars::default(),
+        }
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    pub fn set_table_id(&mut self, id: u16) {
+        self.table_id = id;
+    }
+
+    /// Shows or hides the vertical and horizontal scroll bars
+    pub fn set_scroll_bars(&mut self, vertical: bool, horizontal: bool) {
+        self.scroll_bars.show(vertical, horizontal);
+        self.layout_scroll_bars();
+    }
+
+    pub fn source(&self) -> &dyn TableDataSource {
+        self.source.as_ref()
+    }
+
+    /// Replaces the data, keeping the sort column
+    pub fn set_source(&mut self, source: Box<dyn TableDataSource>) {
+        self.source = source;
+        self.selected = 0;
+        self.scroll_row = 0;
+        self.refresh();
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.source.row_count()
+    }
+
+    /// Source index of the selected row
+    pub fn selected_row(&self) -> Option<usize> {
+        self.source_row(self.selected)
+    }
+
+    /// Selects the row with source index `row` and scrolls it into view
+    pub fn select_row(&mut self, row: usize) {
+        let position = match &self.order {
+            Some(order) => order.iter().position(|&r| r == row),
+            None => (row < self.row_count()).then_some(row),
+        };
+        if let Some(position) = position {
+            self.selected = position;
+            self.ensure_visible();
+        }
+    }
+
+    /// Sort column and whether the order is ascending
+    pub fn sort_order(&self) -> Option<(usize, bool)> {
+        self.sort
+    }
+
+    /// Sorts the rows by `column`, keeping the selected row selected
+    pub fn sort_by(&mut self, column: usize, ascending: bool) {
+        if column >= self.columns.len() {
+            return;
+        }
+        let selected = self.selected_row();
+        self.sort = Some((column, ascending));
+        self.apply_sort();
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+    }
+
+    /// Shows the rows in source order again
+    pub fn clear_sort(&mut self) {
+        let selected = self.selected_row();
+        self.sort = None;
+        self.order = None;
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+    }
+
+    /// Sorts by `column`, or reverses the order if it is already the sort
+    /// column
+    fn toggle_sort(&mut self, column: usize) {
+        let ascending = !matches!(self.sort, Some((sorted, true)) if sorted == column);
+        self.sort_by(column, ascending);
+    }
+
+    fn apply_sort(&mut self) {
+        self.order = self.sort.map(|(column, ascending)| {
+            let source = self.source.as_ref();
+            let mut order: Vec<usize> = (0..source.row_count()).collect();
+            order.sort_by(|&a, &b| {
+                let ordering = source.compare(a, b, column);
+                if ascending { ordering } else { ordering.reverse() }
+            });
+            order
+        });
+    }
+
+    /// Re-reads the row count after data the source shares with others
+    /// changed: the rows are sorted again and the selection and scroll
+    /// position kept in range
+    pub fn refresh(&mut self) {
+        let selected = self.selected_row();
+        self.apply_sort();
+        self.selected = self.selected.min(self.row_count().saturating_sub(1));
+        if let Some(row) = selected.filter(|&row| row < self.row_count()) {
+            self.select_row(row);
+        }
+        self.ensure_visible();
+    }
+
+    /// Source index of display row `position`, if the source still has it.
+    /// Rows added since the last sort have no position until the next.
+    fn source_row(&self, position: usize) -> Option<usize> {
+        let row = match &self.order {
+            Some(order) => order.get(position).copied()?,
+            None => position,
+        };
+        (row < self.row_count()).then_some(row)
+    }
+
+    /// Widths the columns are drawn with: flexible columns share the room
+    /// the others leave, up to their maximum width
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.columns.iter().map(|column| column.width as usize).collect();
+        let used = widths.iter().sum::<usize>() + widths.len().saturating_sub(1);
+        let mut spare = self.body_bounds().width as usize;
+        spare = spare.saturating_sub(used);
+        let flexible: Vec<usize> = (0..self.columns.len()).filter(|&i| self.columns[i].flexible).collect();
+        // Hand out the spare room one cell at a time so capped columns pass
+        // their share on to the others
+        while spare > 0 {
+            let growable: Vec<usize> = flexible
+                .iter()
+                .copied()
+                .filter(|&i| widths[i] < self.columns[i].max_width as usize)
+                .collect();
+            if growable.is_empty() {
+                break;
+            }
+            for i in growable {
+                if spare == 0 {
+                    break;
+                }
+                widths[i] += 1;
+                spare -= 1;
+            }
+        }
+        widths
+    }
+
+    fn content_width(&self) -> usize {
+        let widths = self.column_widths();
+        widths.iter().sum::<usize>() + widths.len().saturating_sub(1)
+    }
+
+    /// Content column of the separator after each column
+    fn separators(&self) -> Vec<usize> {
+        let mut x = 0;
+        self.column_widths()
+            .iter()
+            .map(|width| {
+                x += width;
+                let separator = x;
+                x += 1;
+                separator
+            })
+            .collect()
+    }
+
+    /// Area the rows are drawn in, below the header and clear of the
+    /// scroll bars, in the same coordinates as `bounds`
+    fn body_bounds(&self) -> TRect {
+        let below_header = TRect::new(
+            self.bounds.x,
+            self.bounds.y + 1,
+            self.bounds.width,
+            self.bounds.height.saturating_sub(1),
+        );
+        self.scroll_bars.content_area(below_header)
+    }
+
+    fn page_height(&self) -> usize {
+        self.body_bounds().height as usize
+    }
+
+    fn max_scroll_row(&self) -> usize {
+        self.row_count().saturating_sub(self.page_height())
+    }
+
+    fn max_scroll_x(&self) -> usize {
+        self.content_width().saturating_sub(self.body_bounds().width as usize)
+    }
+
+    fn layout_scroll_bars(&mut self) {
+        self.scroll_bars.layout(self.body_bounds());
+        self.sync_scroll_bars();
+    }
+
+    /// Moves the scroll bars to the current scroll position
+    fn sync_scroll_bars(&mut self) {
+        let max = (self.max_scroll_x(), self.max_scroll_row());
+        let page = (self.body_bounds().width as usize, self.page_height());
+        self.scroll_bars.sync((self.scroll_x, self.scroll_row), max, page);
+    }
+
+    fn ensure_visible(&mut self) {
+        let page = self.page_height().max(1);
+        if self.selected < self.scroll_row {
+            self.scroll_row = self.selected;
+        } else if self.selected >= self.scroll_row + page {
+            self.scroll_row = self.selected + 1 - page;
+        }
+        self.scroll_row = self.scroll_row.min(self.max_scroll_row());
+        self.scroll_x = self.scroll_x.min(self.max_scroll_x());
+        self.sync_scroll_bars();
+    }
+
+    fn activate(&self, queue: &TEventQueue) {
+        if let Some(row) = self.selected_row() {
+            queue.put_event(TEvent::ListItemActivated(self.table_id, row));
+        }
+    }
+
+    /// Formats display row `position`, or the header if `None`, as one
+    /// line of content
+    fn format_row(&self, position: Option<usize>, widths: &[usize]) -> String {
+        let mut line = String::new();
+        for (i, (column, &width)) in self.columns.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.push('│');
+            }
+            let text = match position {
+                Some(position) => {
+                    self.source_row(position).map(|row| self.source.cell_text(row, i)).unwrap_or_default()
+                }
+                None => match self.sort {
+                    Some((sorted, ascending)) if sorted == i => {
+                        let arrow = if ascending { '▲' } else { '▼' };
+                        let title: String = column.title.chars().take(width.saturating_sub(1)).collect();
+                        format!("{:<w$}{}", title, arrow, w = width.saturating_sub(1))
+                    }
+                    _ => column.title.clone(),
+                },
+            };
+            line.push_str(&align_text(&text, width, column.align));
+        }
+        line
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, queue: &TEventQueue) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let last = self.row_count().saturating_sub(1);
+        let page = self.page_height().max(1);
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(page),
+            KeyCode::PageDown => self.selected = (self.selected + page).min(last),
+            KeyCode::Home if ctrl => self.selected = 0,
+            KeyCode::End if ctrl => self.selected = last,
+            KeyCode::Home => self.scroll_x = 0,
+            KeyCode::End => self.scroll_x = self.max_scroll_x(),
+            KeyCode::Left => self.scroll_x = self.scroll_x.saturating_sub(if ctrl { page } else { 1 }),
+            KeyCode::Right => self.scroll_x = (self.scroll_x + if ctrl { page } else { 1 }).min(self.max_scroll_x()),
+            KeyCode::Enter => self.activate(queue),
+            KeyCode::Char(ch) if alt && ch.is_ascii_digit() && ch != '0' => {
+                self.toggle_sort(ch as usize - '1' as usize);
+            }
+            _ => return,
+        }
+        self.ensure_visible();
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, queue: &TEventQueue) {
+        if let Some(position) = self.scroll_bars.handle_mouse(&mouse, (self.scroll_x, self.scroll_row)) {
+            (self.scroll_x, self.scroll_row) = position;
+            return;
+        }
+
+        // Left of the table this is 0, so a dragged column shrinks to its
+        // minimum width
+        let content_x = (mouse.column as usize + self.scroll_x).saturating_sub(self.bounds.x as usize);
+        match mouse.kind {
+            MouseEventKind::Drag(MouseButton::Left) if self.resizing.is_some() => {
+                let Some(column) = self.resizing else { return };
+                let start = self.separators()[column] - self.column_widths()[column];
+                let spec = &mut self.columns[column];
+                let width = content_x.saturating_sub(start).clamp(spec.min_width as usize, spec.max_width as usize);
+                spec.width = width as u16;
+                spec.flexible = false;
+                self.ensure_visible();
+                return;
+            }
+            MouseEventKind::Up(_) => {
+                self.resizing = None;
+                return;
+            }
+            _ => {}
+        }
+
+        let pt = TPoint { x: mouse.column, y: mouse.row };
+        if !self.bounds.contains(pt) {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if mouse.row == self.bounds.y => {
+                let separators = self.separators();
+                if let Some(column) = separators.iter().position(|&x| x == content_x) {
+                    self.resizing = Some(column);
+                } else if let Some(column) = separators.iter().position(|&x| content_x < x) {
+                    self.toggle_sort(column);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let body = self.body_bounds();
+                if !body.contains(pt) {
+                    return;
+                }
+                let position = self.scroll_row + (mouse.row - body.y) as usize;
+                if position >= self.row_count() {
+                    return;
+                }
+                self.selected = position;
+                let double = self
+                    .last_click
+                    .is_some_and(|(at, clicked)| clicked == position && at.elapsed() <= DOUBLE_CLICK_TIME);
+                if double {
+                    self.last_click = None;
+                    self.activate(queue);
+                } else {
+                    self.last_click = Some((Instant::now(), position));
+                }
+            }
+            MouseEventKind::ScrollUp => self.scroll_row = self.scroll_row.saturating_sub(WHEEL_STEP),
+            MouseEventKind::ScrollDown => self.scroll_row = (self.scroll_row + WHEEL_STEP).min(self.max_scroll_row()),
+            MouseEventKind::ScrollLeft => self.scroll_x = self.scroll_x.saturating_sub(WHEEL_STEP),
+            MouseEventKind::ScrollRight => self.scroll_x = (self.scroll_x + WHEEL_STEP).min(self.max_scroll_x()),
+            _ => return,
+        }
+        self.sync_scroll_bars();
+    }
+}
+
+impl TView for TTable {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        buffer.fill(TRect::new(x, y, self.bounds.width, self.bounds.height), ' ', TABLE_FG, TABLE_BG);
+        let body = self.body_bounds();
+        let width = body.width as usize;
+        let widths = self.column_widths();
+        let visible = |line: String| -> String { line.chars().skip(self.scroll_x).take(width).collect() };
+
+        buffer.write_styled(x, y, &visible(self.format_row(None, &widths)), HEADER_FG, HEADER_BG);
+        let end = self.row_count().min(self.scroll_row + self.page_height());
+        for (row, position) in (self.scroll_row..end).enumerate() {
+            let (fg, bg) = if position == self.selected && self.focused {
+                (SELECTED_FG, SELECTED_BG)
+            } else {
+                (TABLE_FG, TABLE_BG)
+            };
+            let line = format!("{:<width$}", visible(self.format_row(Some(position), &widths)), width = width);
+            buffer.write_styled(x, y + 1 + row as u16, &line, fg, bg);
+        }
+
+        self.scroll_bars.draw(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        if self.order.as_ref().is_some_and(|order| order.len() != self.row_count()) {
+            self.refresh();
+        }
+        let before = self.selected_row();
+        match event {
+            TEvent::Key(key) => self.handle_key(key, queue),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse, queue),
+            _ => {}
+        }
+        if let Some(row) = self.selected_row().filter(|&row| Some(row) != before) {
+            queue.put_event(TEvent::ListSelectionChanged(self.table_id, row));
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+        self.layout_scroll_bars();
+        self.ensure_visible();
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.resizing = None;
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    /// Enter activates the selected row
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Enter
+    }
+
+    /// The source index of the selected row
+    fn get_data(&self) -> Option<TData> {
+        Some(TData::Index(self.selected_row().unwrap_or(0)))
+    }
+
+    fn set_data(&mut self, data: TData) {
+        if let TData::Index(row) = data {
+            self.select_row(row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Rows numbered from 0, as many as the shared count says
+    struct Numbers(Rc<Cell<usize>>);
+
+    impl TableDataSource for Numbers {
+        fn row_count(&self) -> usize {
+            self.0.get()
+        }
+
+        fn cell_text(&self, row: usize, _column: usize) -> String {
+            row.to_string()
+        }
+    }
+
+    fn table(width: u16, columns: Vec<TColumn>) -> TTable {
+        TTable::new(TRect::new(0, 0, width, 5), columns, Box::new(Vec::<Vec<String>>::new()))
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> TEvent {
+        TEvent::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })
+    }
+
+    #[test]
+    fn flexible_columns_share_the_spare_room() {
+        let table = table(30, vec![
+            TColumn::new("a", 5),
+            TColumn::new("b", 4).with_flexible(true),
+            TColumn::new("c", 4).with_flexible(true),
+        ]);
+        // 15 cells used with the separators, 15 spare
+        assert_eq!(table.column_widths(), vec![5, 12, 11]);
+    }
+
+    #[test]
+    fn capped_columns_pass_their_share_on() {
+        let table = table(30, vec![
+            TColumn::new("a", 4).with_limits(1, 6).with_flexible(true),
+            TColumn::new("b", 4).with_flexible(true),
+            TColumn::new("c", 4).with_limits(1, 4).with_flexible(true),
+        ]);
+        assert_eq!(table.column_widths(), vec![6, 18, 4]);
+    }
+
+    #[test]
+    fn columns_keep_their_width_without_spare_room() {
+        let table = table(8, vec![TColumn::new("a", 5), TColumn::new("b", 5).with_flexible(true)]);
+        assert_eq!(table.column_widths(), vec![5, 5]);
+    }
+
+    #[test]
+    fn rows_added_after_sorting_do_not_panic() {
+        let rows = Rc::new(Cell::new(3));
+        let mut table = TTable::new(TRect::new(0, 0, 20, 10), vec![TColumn::new("n", 5)], Box::new(Numbers(rows.clone())));
+        table.sort_by(0, false);
+        rows.set(5);
+        table.select_row(2);
+        table.selected = 4;
+        assert_eq!(table.selected_row(), None);
+        table.draw(&mut ScreenBuffer::new(20, 10), (0, 0));
+
+        let queue = TEventQueue::new();
+        table.handle_event(TEvent::Key(KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL)), &queue);
+        assert_eq!(table.selected_row(), Some(4));
+    }
+
+    #[test]
+    fn dragging_left_of_a_column_shrinks_it_to_its_minimum() {
+        let mut table = table(30, vec![TColumn::new("a", 5), TColumn::new("b", 5).with_limits(2, 10)]);
+        table.bounds.x = 4;
+        let queue = TEventQueue::new();
+        table.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 4 + 11, 0), &queue);
+        table.handle_event(mouse(MouseEventKind::Drag(MouseButton::Left), 0, 0), &queue);
+        assert_eq!(table.columns[1].width, 2);
+    }
+}
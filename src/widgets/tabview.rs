@@ -0,0 +1,589 @@
+use crate::core::command::CM_RELEASED_FOCUS;
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{mouse_to_local, TEvent, TEventQueue};
+use crate::core::view::{TData, TView};
+use crate::core::focus::FocusManager;
+use crate::core::menu::hotkey_of;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+
+const TAB_FG: Color = Color::Black;
+const TAB_BG: Color = Color::Cyan;
+const CURRENT_FG: Color = Color::White;
+const CURRENT_BG: Color = Color::Blue;
+const HOTKEY_FG: Color = Color::Yellow;
+
+/// Rows above the pages: the tab strip and the line below it
+const STRIP_HEIGHT: u16 = 2;
+
+/// One page of a `TTabView`: a title with an optional `~x~` hotkey and the
+/// views shown while the page is current. The page remembers which of its
+/// views had focus.
+pub struct TTabPage {
+    pub title: String,
+    pub children: Vec<Box<dyn TView>>,
+    pub focus: FocusManager,
+}
+
+impl TTabPage {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            children: Vec::new(),
+            focus: FocusManager::new(),
+        }
+    }
+
+    /// Adds a view, in the page's coordinates. The first view that can
+    /// take focus gets it when the page is shown.
+    pub fn add_child(&mut self, mut child: Box<dyn TView>) {
+        child.set_focus(false);
+        let focusable = self.children.iter().any(|child| child.is_focusable());
+        if !focusable && child.is_focusable() {
+            self.focus.index = self.children.len();
+        }
+        self.children.push(child);
+    }
+
+    pub fn hotkey(&self) -> Option<char> {
+        hotkey_of(&self.title)
+    }
+
+    /// Width of the title on the tab strip, hotkey markers left out
+    fn title_width(&self) -> u16 {
+        self.title.chars().filter(|&c| c != '~').count() as u16
+    }
+
+    /// Next view after the focused one that can take focus, going
+    /// backwards if `forward` is false
+    fn next_focusable(&self, forward: bool) -> Option<usize> {
+        let current = self.focus.index;
+        if forward {
+            (current + 1..self.children.len()).find(|&i| self.children[i].is_focusable())
+        } else {
+            (0..current).rev().find(|&i| self.children[i].is_focusable())
+        }
+    }
+}
+
+/// Pages of views behind a strip of tabs, one page shown at a time.
+/// Ctrl+PgUp/PgDn, Ctrl+Tab and Ctrl+Shift+Tab switch to the previous or
+/// next page, as do a click on a tab and Alt with a tab's hotkey. Each page
+/// keeps its focused view, so coming back to a page focuses it again. Tab
+/// moves between the page's views and, inside a dialog, on to the dialog's
+/// other controls after the last one.
+pub struct TTabView {
+    pub bounds: TRect,
+    pub pages: Vec<TTabPage>,
+    pub current: usize,          // Page shown
+    pub focused: bool,
+    mouse_target: Option<usize>, // child of the current page that got the last button press
+}
+
+impl TTabView {
+    pub fn new(bounds: TRect) -> Self {
+        Self {
+            bounds,
+            pages: Vec::new(),
+            current: 0,
+            focused: true,
+            mouse_target: None,
+        }
+    }
+
+    /// Adds a page after the others; its views are placed below the tab
+    /// strip, in the page's coordinates
+    pub fn add_page(&mut self, mut page: TTabPage) {
+        if self.pages.is_empty() && self.focused {
+            if let Some(child) = page.focus.current_mut(&mut page.children) {
+                child.set_focus(true);
+            }
+        }
+        self.pages.push(page);
+    }
+
+    /// Area the pages are drawn in, in the same coordinates as `bounds`
+    pub fn page_bounds(&self) -> TRect {
+        TRect::new(
+            self.bounds.x,
+            self.bounds.y + STRIP_HEIGHT,
+            self.bounds.width,
+            self.bounds.height.saturating_sub(STRIP_HEIGHT),
+        )
+    }
+
+    /// Shows the page at `index` if the focused view of the current page
+    /// may lose focus. Returns true if the page is shown afterwards.
+    pub fn select_page(&mut self, index: usize, queue: &TEventQueue) -> bool {
+        if index >= self.pages.len() {
+            return false;
+        }
+        if index != self.current {
+            if !self.release_focus(queue) {
+                return false;
+            }
+            self.show_focus(false);
+            self.current = index;
+            self.mouse_target = None;
+            self.show_focus(self.focused);
+        }
+        true
+    }
+
+    /// Shows the next page, or the previous one if `forward` is false,
+    /// wrapping around at either end
+    fn cycle_page(&mut self, forward: bool, queue: &TEventQueue) {
+        let count = self.pages.len();
+        if count > 1 {
+            let index = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+            self.select_page(index, queue);
+        }
+    }
+
+    /// Shows or hides focus on the focused view of the current page
+    fn show_focus(&mut self, focused: bool) {
+        if let Some(page) = self.pages.get_mut(self.current) {
+            if let Some(child) = page.focus.current_mut(&mut page.children) {
+                child.set_focus(focused);
+            }
+        }
+    }
+
+    /// Asks the focused view of the current page whether it may lose focus
+    fn release_focus(&mut self, queue: &TEventQueue) -> bool {
+        self.pages.get_mut(self.current).is_none_or(|page| {
+            page.focus
+                .current_mut(&mut page.children)
+                .is_none_or(|child| child.valid(CM_RELEASED_FOCUS, queue))
+        })
+    }
+
+    /// Focuses the view at `index` of the current page if it can take
+    /// focus and the focused view's data is valid. Returns true if `index`
+    /// has focus afterwards.
+    fn focus_child(&mut self, index: usize, queue: &TEventQueue) -> bool {
+        let Some(focus_index) = self.pages.get(self.current).map(|page| page.focus.index) else {
+            return false;
+        };
+        if index != focus_index {
+            if !self.release_focus(queue) {
+                return false;
+            }
+            let page = &mut self.pages[self.current];
+            page.focus.set_focus(&mut page.children, index);
+            if !self.focused {
+                self.show_focus(false);
+            }
+        }
+        self.pages[self.current].focus.index == index
+    }
+
+    /// Moves focus to the next view of the current page, wrapping around
+    /// after the last one
+    fn focus_next(&mut self, forward: bool, queue: &TEventQueue) {
+        let Some(page) = self.pages.get(self.current) else {
+            return;
+        };
+        let wrapped = if forward {
+            (0..page.children.len()).find(|&i| page.children[i].is_focusable())
+        } else {
+            (0..page.children.len()).rev().find(|&i| page.children[i].is_focusable())
+        };
+        if let Some(index) = page.next_focusable(forward).or(wrapped) {
+            self.focus_child(index, queue);
+        }
+    }
+
+    /// Tab the mouse is over, from a point in the same coordinates as
+    /// `bounds`
+    fn tab_at(&self, pt: TPoint) -> Option<usize> {
+        if pt.y != self.bounds.y {
+            return None;
+        }
+        let mut x = self.bounds.x;
+        for (index, page) in self.pages.iter().enumerate() {
+            let width = page.title_width() + 2;
+            if pt.x >= x && pt.x < x + width {
+                return Some(index);
+            }
+            x += width + 1;
+        }
+        None
+    }
+
+    /// Sends a mouse event to the view under the pointer on the current
+    /// page, in the page's coordinates. A view that got a button press
+    /// keeps receiving events until the button is released.
+    fn route_mouse(&mut self, mouse: MouseEvent, queue: &TEventQueue) {
+        let area = self.page_bounds();
+        let local = mouse_to_local(mouse, (area.x, area.y));
+        let pt = TPoint { x: local.column, y: local.row };
+        let Some(page) = self.pages.get(self.current) else {
+            return;
+        };
+
+        let target = match mouse.kind {
+            MouseEventKind::Down(_) => {
+                let mut hit = page.children.iter().rposition(|child| child.get_bounds().contains(pt));
+                if let Some(index) = hit {
+                    if page.children[index].is_focusable() && !self.focus_child(index, queue) {
+                        hit = None;
+                    }
+                }
+                self.mouse_target = hit;
+                hit
+            }
+            MouseEventKind::Drag(_) | MouseEventKind::Up(_) => self.mouse_target,
+            _ => page.children.iter().rposition(|child| child.get_bounds().contains(pt)),
+        };
+        if let MouseEventKind::Up(_) = mouse.kind {
+            self.mouse_target = None;
+        }
+
+        if let Some(index) = target {
+            self.pages[self.current].children[index].handle_event(TEvent::Mouse(local), queue);
+        }
+    }
+
+    fn draw_strip(&self, buffer: &mut ScreenBuffer, x: u16, y: u16) {
+        let right = x + self.bounds.width;
+        let mut tab_x = x;
+        for (index, page) in self.pages.iter().enumerate() {
+            let (fg, bg) = if index == self.current { (CURRENT_FG, CURRENT_BG) } else { (TAB_FG, TAB_BG) };
+            let mut col = tab_x;
+            let mut in_hotkey = false;
+            for ch in format!(" {} ", page.title).chars() {
+                if ch == '~' {
+                    in_hotkey = !in_hotkey;
+                    continue;
+                }
+                if col < right {
+                    buffer.set_styled(col, y, ch, if in_hotkey { HOTKEY_FG } else { fg }, bg);
+                }
+                col += 1;
+            }
+            tab_x = col + 1;
+        }
+        buffer.write_str(x, y + 1, &"─".repeat(self.bounds.width as usize));
+    }
+}
+
+impl TView for TTabView {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        buffer.fill(TRect::new(x, y, self.bounds.width, self.bounds.height), ' ', Color::Reset, Color::Reset);
+        self.draw_strip(buffer, x, y);
+
+        if let Some(page) = self.pages.get(self.current) {
+            let area = self.page_bounds();
+            let offset = (offset.0 + area.x, offset.1 + area.y);
+            for child in &page.children {
+                child.draw(buffer, offset);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        if let TEvent::Mouse(mouse) = event {
+            let pt = TPoint { x: mouse.column, y: mouse.row };
+            match self.tab_at(pt) {
+                Some(index) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    self.select_page(index, queue);
+                }
+                Some(_) => {}
+                None => self.route_mouse(mouse, queue),
+            }
+            return;
+        }
+
+        if let TEvent::Key(key) = &event {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::PageDown | KeyCode::Tab if ctrl => return self.cycle_page(true, queue),
+                KeyCode::PageUp | KeyCode::BackTab if ctrl => return self.cycle_page(false, queue),
+                KeyCode::Tab => return self.focus_next(true, queue),
+                KeyCode::BackTab => return self.focus_next(false, queue),
+                KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => {
+                    let hotkey = c.to_ascii_lowercase();
+                    if let Some(index) = self.pages.iter().position(|page| page.hotkey() == Some(hotkey)) {
+                        self.select_page(index, queue);
+                        return;
+                    }
+                    let Some(page) = self.pages.get(self.current) else {
+                        return;
+                    };
+                    if let Some(index) = page.children.iter().position(|child| child.hotkey() == Some(hotkey)) {
+                        if !page.children[index].is_focusable() || self.focus_child(index, queue) {
+                            self.pages[self.current].children[index].handle_event(event, queue);
+                        }
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(page) = self.pages.get_mut(self.current) {
+            if let Some(focused) = page.focus.current_mut(&mut page.children) {
+                focused.handle_event(event, queue);
+            }
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.show_focus(focused);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        let page = self.pages.get(self.current)?;
+        page.focus.current(&page.children).and_then(|child| child.help_context())
+    }
+
+    /// Page switching, and Tab until the last view of the page has focus;
+    /// other keys as the focused view takes them
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        let Some(page) = self.pages.get(self.current) else {
+            return false;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Tab | KeyCode::BackTab if ctrl => true,
+            KeyCode::Tab => page.next_focusable(true).is_some(),
+            KeyCode::BackTab => page.next_focusable(false).is_some(),
+            _ => page.focus.current(&page.children).is_some_and(|child| child.takes_key(key)),
+        }
+    }
+
+    /// Collects the data of each page's views as a record per page
+    fn get_data(&self) -> Option<TData> {
+        let pages = self.pages.iter().map(|page| {
+            TData::Record(page.children.iter().filter_map(|child| child.get_data()).collect())
+        });
+        Some(TData::Record(pages.collect()))
+    }
+
+    /// Hands out the values of a record per page, in the order `get_data`
+    /// returns them
+    fn set_data(&mut self, data: TData) {
+        if let TData::Record(records) = data {
+            for (page, record) in self.pages.iter_mut().zip(records) {
+                if let TData::Record(values) = record {
+                    let children = page.children.iter_mut().filter(|child| child.get_data().is_some());
+                    for (child, value) in children.zip(values) {
+                        child.set_data(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks the views of every page, showing the page of the first one
+    /// that is not valid and focusing it. Only the focused view is asked
+    /// when the tab view itself loses focus.
+    fn valid(&mut self, command: u16, queue: &TEventQueue) -> bool {
+        if command == CM_RELEASED_FOCUS {
+            return self.release_focus(queue);
+        }
+        for index in 0..self.pages.len() {
+            let page = &mut self.pages[index];
+            if let Some(child) = page.children.iter_mut().position(|child| !child.valid(command, queue)) {
+                self.show_focus(false);
+                self.current = index;
+                let page = &mut self.pages[index];
+                page.focus.set_focus(&mut page.children, child);
+                self.show_focus(self.focused);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::command::CM_OK;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Control with optional data whose validity and focus the test sees
+    struct Field {
+        data: Option<TData>,
+        valid: Rc<Cell<bool>>,
+        focused: Rc<Cell<bool>>,
+    }
+
+    impl TView for Field {
+        fn draw(&self, _buffer: &mut ScreenBuffer, _offset: (u16, u16)) {}
+        fn handle_event(&mut self, _event: TEvent, _queue: &TEventQueue) {}
+        fn get_bounds(&self) -> TRect {
+            TRect::new(0, 0, 10, 1)
+        }
+        fn set_bounds(&mut self, _bounds: TRect) {}
+        fn set_focus(&mut self, focused: bool) {
+            self.focused.set(focused);
+        }
+        fn is_focusable(&self) -> bool {
+            self.data.is_some()
+        }
+        fn valid(&mut self, _command: u16, _queue: &TEventQueue) -> bool {
+            self.valid.get()
+        }
+        fn get_data(&self) -> Option<TData> {
+            self.data.clone()
+        }
+        fn set_data(&mut self, data: TData) {
+            self.data = Some(data);
+        }
+    }
+
+    /// Validity and focus flags of a field added to a page
+    struct Handle {
+        valid: Rc<Cell<bool>>,
+        focused: Rc<Cell<bool>>,
+    }
+
+    fn add_field(page: &mut TTabPage, data: Option<TData>) -> Handle {
+        let handle = Handle { valid: Rc::new(Cell::new(true)), focused: Rc::new(Cell::new(false)) };
+        page.add_child(Box::new(Field { data, valid: Rc::clone(&handle.valid), focused: Rc::clone(&handle.focused) }));
+        handle
+    }
+
+    fn text(value: &str) -> Option<TData> {
+        Some(TData::Text(value.to_string()))
+    }
+
+    /// Pages "~G~eneral" with fields a, a label and b; "~C~olors" with an
+    /// index; and "~M~ore" with c
+    fn tab_view() -> (TTabView, Vec<Handle>) {
+        let mut general = TTabPage::new("~G~eneral");
+        let a = add_field(&mut general, text("a"));
+        add_field(&mut general, None);
+        let b = add_field(&mut general, text("b"));
+        let mut colors = TTabPage::new("~C~olors");
+        let index = add_field(&mut colors, Some(TData::Index(1)));
+        let mut more = TTabPage::new("~M~ore");
+        let c = add_field(&mut more, text("c"));
+
+        let mut view = TTabView::new(TRect::new(0, 0, 40, 10));
+        view.add_page(general);
+        view.add_page(colors);
+        view.add_page(more);
+        (view, vec![a, b, index, c])
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> TEvent {
+        TEvent::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn ctrl_keys_cycle_pages_and_wrap() {
+        let queue = TEventQueue::new();
+        let (mut view, _) = tab_view();
+        let mut visited = Vec::new();
+        for _ in 0..3 {
+            view.handle_event(key(KeyCode::PageDown, KeyModifiers::CONTROL), &queue);
+            visited.push(view.current);
+        }
+        assert_eq!(visited, vec![1, 2, 0]);
+
+        view.handle_event(key(KeyCode::PageUp, KeyModifiers::CONTROL), &queue);
+        assert_eq!(view.current, 2);
+        view.handle_event(key(KeyCode::Tab, KeyModifiers::CONTROL), &queue);
+        assert_eq!(view.current, 0);
+        view.handle_event(key(KeyCode::BackTab, KeyModifiers::CONTROL | KeyModifiers::SHIFT), &queue);
+        assert_eq!(view.current, 2);
+        view.handle_event(key(KeyCode::Char('c'), KeyModifiers::ALT), &queue);
+        assert_eq!(view.current, 1);
+    }
+
+    #[test]
+    fn invalid_view_keeps_its_page() {
+        let queue = TEventQueue::new();
+        let (mut view, fields) = tab_view();
+        fields[0].valid.set(false);
+        view.handle_event(key(KeyCode::PageDown, KeyModifiers::CONTROL), &queue);
+        view.handle_event(key(KeyCode::Char('m'), KeyModifiers::ALT), &queue);
+        assert!(!view.select_page(1, &queue));
+        assert_eq!(view.current, 0);
+
+        fields[0].valid.set(true);
+        assert!(view.select_page(1, &queue));
+    }
+
+    #[test]
+    fn page_keeps_its_focused_view() {
+        let queue = TEventQueue::new();
+        let (mut view, fields) = tab_view();
+        assert!(fields[0].focused.get());
+        view.handle_event(key(KeyCode::Tab, KeyModifiers::NONE), &queue);
+        assert!(fields[1].focused.get() && !fields[0].focused.get());
+
+        view.select_page(1, &queue);
+        assert!(!fields[1].focused.get() && fields[2].focused.get());
+        view.select_page(0, &queue);
+        assert!(fields[1].focused.get() && !fields[2].focused.get());
+        assert_eq!(view.pages[0].focus.index, 2);
+    }
+
+    #[test]
+    fn tab_leaves_view_after_last_control() {
+        let queue = TEventQueue::new();
+        let (mut view, _) = tab_view();
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+        let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert!(view.takes_key(&tab));
+        assert!(!view.takes_key(&back_tab));
+
+        view.handle_event(TEvent::Key(tab), &queue);
+        assert!(!view.takes_key(&tab));
+        assert!(view.takes_key(&back_tab));
+        // Page switching is always the tab view's own
+        assert!(view.takes_key(&KeyEvent::new(KeyCode::Tab, KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn data_is_a_record_per_page() {
+        let (mut view, _) = tab_view();
+        let record = |values: Vec<TData>| TData::Record(values);
+        let text = |value: &str| TData::Text(value.to_string());
+        assert_eq!(
+            view.get_data(),
+            Some(record(vec![record(vec![text("a"), text("b")]), record(vec![TData::Index(1)]), record(vec![text("c")])]))
+        );
+
+        view.set_data(record(vec![record(vec![text("x"), text("y")]), record(vec![TData::Index(4)])]));
+        assert_eq!(
+            view.get_data(),
+            Some(record(vec![record(vec![text("x"), text("y")]), record(vec![TData::Index(4)]), record(vec![text("c")])]))
+        );
+    }
+
+    #[test]
+    fn valid_shows_page_of_first_invalid_view() {
+        let queue = TEventQueue::new();
+        let (mut view, fields) = tab_view();
+        fields[3].valid.set(false);
+        assert!(!view.valid(CM_OK, &queue));
+        assert_eq!(view.current, 2);
+        assert!(fields[3].focused.get() && !fields[0].focused.get());
+
+        fields[3].valid.set(true);
+        assert!(view.valid(CM_OK, &queue));
+    }
+}
@@ -0,0 +1,561 @@
+use crate::core::rect::{TPoint, TRect};
+use crate::core::event::{TEvent, TEventQueue};
+use crate::core::input::{DOUBLE_CLICK_TIME, SEARCH_TIMEOUT, WHEEL_STEP};
+use crate::core::view::TView;
+use crate::ui::screenbuffer::ScreenBuffer;
+use crate::widgets::scrollbar::ScrollBars;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::Color;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+const SELECTED_FG: Color = Color::Black;
+const SELECTED_BG: Color = Color::Cyan;
+
+/// Nodes shown by a `TTreeView`. A node is named by its path: the child
+/// index at each level, starting from the top level, so the empty path is
+/// the invisible root above the top-level nodes. Children are only asked
+/// for once their parent is expanded, and the answers are kept until the
+/// tree view is told the data changed.
+pub trait TreeDataSource {
+    /// Number of children of the node at `path`
+    fn child_count(&self, path: &[usize]) -> usize;
+
+    fn node_text(&self, path: &[usize]) -> String;
+
+    /// Whether the node can be expanded. Overriding this lets a node show
+    /// a `+` without loading its children.
+    fn has_children(&self, path: &[usize]) -> bool {
+        self.child_count(path) > 0
+    }
+}
+
+/// Child counts and expandability read from the source, kept until the
+/// data changes so that expanding or collapsing a node reads only the
+/// nodes it shows for the first time
+#[derive(Default)]
+struct NodeCache {
+    child_counts: HashMap<Vec<usize>, usize>,
+    has_children: HashMap<Vec<usize>, bool>,
+}
+
+impl NodeCache {
+    fn child_count(&mut self, source: &dyn TreeDataSource, path: &[usize]) -> usize {
+        if let Some(&count) = self.child_counts.get(path) {
+            return count;
+        }
+        let count = source.child_count(path);
+        self.child_counts.insert(path.to_vec(), count);
+        count
+    }
+
+    fn has_children(&mut self, source: &dyn TreeDataSource, path: &[usize]) -> bool {
+        if let Some(&count) = self.child_counts.get(path) {
+            return count > 0;
+        }
+        if let Some(&has_children) = self.has_children.get(path) {
+            return has_children;
+        }
+        let has_children = source.has_children(path);
+        self.has_children.insert(path.to_vec(), has_children);
+        has_children
+    }
+
+    fn clear(&mut self) {
+        self.child_counts.clear();
+        self.has_children.clear();
+    }
+}
+
+/// A node on screen, with what is needed to draw its connector lines
+struct VisibleNode {
+    path: Vec<usize>,
+    has_children: bool,
+    last: Vec<bool>,    // Whether the node and each ancestor is the last of its siblings, top level first
+}
+
+/// A tree of nodes drawn with box-drawing connector lines. `+` or Right
+/// expands a node and `-` or Left collapses it; a click on the `+`/`-`
+/// marker toggles it. Typing jumps to the first visible node starting with
+/// the typed text. Changes to the selected node post
+/// `TreeSelectionChanged` and Enter or a double-click posts
+/// `TreeNodeActivated`.
+///
+/// Expanded nodes are remembered by path. A node inserted or removed
+/// before an expanded sibling therefore moves the expansion to whichever
+/// node now has that path; sources whose nodes move should collapse and
+/// expand them again after calling `refresh`.
+pub struct TTreeView {
+    pub bounds: TRect,
+    pub focused: bool,
+    pub help_context: Option<u16>,
+    pub tree_id: u16,               // Sent with the tree's events to tell trees apart
+    source: Box<dyn TreeDataSource>,
+    expanded: HashSet<Vec<usize>>,
+    cache: NodeCache,
+    visible: Vec<VisibleNode>,
+    selected: usize,                // Selected visible node
+    scroll_offset: usize,
+    search: String,
+    last_search: Option<Instant>,
+    last_click: Option<(Instant, usize)>,
+    scroll_bars: ScrollBars,
+}
+
+impl TTreeView {
+    pub fn new(bounds: TRect, source: Box<dyn TreeDataSource>) -> Self {
+        let mut tree = Self {
+            bounds,
+            focused: true,
+            help_context: None,
+            tree_id: 0,
+            source,
+            expanded: HashSet::new(),
+            cache: NodeCache::default(),
+            visible: Vec::new(),
+            selected: 0,
+            scroll_offset: 0,
+            search: String::new(),
+            last_search: None,
+            last_click: None,
+            scroll_bars: ScrollBars::default(),
+        };
+        tree.rebuild();
+        tree
+    }
+
+    pub fn set_help_context(&mut self, ctx: u16) {
+        self.help_context = Some(ctx);
+    }
+
+    pub fn set_tree_id(&mut self, id: u16) {
+        self.tree_id = id;
+    }
+
+    /// Shows or hides a scroll bar in the right column
+    pub fn set_scroll_bar(&mut self, show: bool) {
+        self.scroll_bars.show(show, false);
+        self.layout_scroll_bar();
+    }
+
+    pub fn source(&self) -> &dyn TreeDataSource {
+        self.source.as_ref()
+    }
+
+    /// Replaces the data, collapsing every node
+    pub fn set_source(&mut self, source: Box<dyn TreeDataSource>) {
+        self.source = source;
+        self.expanded.clear();
+        self.cache.clear();
+        self.selected = 0;
+        self.scroll_offset = 0;
+        self.rebuild();
+    }
+
+    /// Re-reads the visible nodes after data the source shares with others
+    /// changed, keeping the selected path if it still exists
+    pub fn refresh(&mut self) {
+        self.cache.clear();
+        self.relist();
+    }
+
+    /// Path of the selected node
+    pub fn selected_path(&self) -> Option<Vec<usize>> {
+        self.visible.get(self.selected).map(|node| node.path.clone())
+    }
+
+    /// Selects the node at `path`, expanding its ancestors. Returns false
+    /// if there is no such node.
+    pub fn select_path(&mut self, path: &[usize]) -> bool {
+        for depth in 1..path.len() {
+            self.expanded.insert(path[..depth].to_vec());
+        }
+        self.rebuild();
+        match self.visible.iter().position(|node| node.path == path) {
+            Some(index) => {
+                self.selected = index;
+                self.ensure_visible();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_expanded(&self, path: &[usize]) -> bool {
+        self.expanded.contains(path)
+    }
+
+    pub fn expand(&mut self, path: &[usize]) {
+        if self.cache.has_children(self.source.as_ref(), path) && self.expanded.insert(path.to_vec()) {
+            self.relist();
+        }
+    }
+
+    /// Collapses the node at `path`; a selected descendant passes the
+    /// selection to it
+    pub fn collapse(&mut self, path: &[usize]) {
+        if self.expanded.remove(path) {
+            let selected = self.selected_path().map(|selected| {
+                if selected.starts_with(path) { path.to_vec() } else { selected }
+            });
+            self.rebuild();
+            if let Some(selected) = selected {
+                self.select_path(&selected);
+            }
+        }
+    }
+
+    /// Lists the visible nodes again, keeping the selected node selected
+    fn relist(&mut self) {
+        let selected = self.selected_path();
+        self.rebuild();
+        if let Some(index) = selected.and_then(|path| self.visible.iter().position(|node| node.path == path)) {
+            self.selected = index;
+            self.ensure_visible();
+        }
+    }
+
+    /// Lists the nodes that are shown: the top level and the children of
+    /// expanded nodes, depth first
+    fn rebuild(&mut self) {
+        fn add(
+            source: &dyn TreeDataSource,
+            cache: &mut NodeCache,
+            expanded: &HashSet<Vec<usize>>,
+            path: &mut Vec<usize>,
+            last: &mut Vec<bool>,
+            out: &mut Vec<VisibleNode>,
+        ) {
+            let count = cache.child_count(source, path);
+            for index in 0..count {
+                path.push(index);
+                last.push(index + 1 == count);
+                let has_children = cache.has_children(source, path);
+                out.push(VisibleNode { path: path.clone(), has_children, last: last.clone() });
+                if has_children && expanded.contains(path.as_slice()) {
+                    add(source, cache, expanded, path, last, out);
+                }
+                path.pop();
+                last.pop();
+            }
+        }
+
+        let mut visible = Vec::new();
+        add(self.source.as_ref(), &mut self.cache, &self.expanded, &mut Vec::new(), &mut Vec::new(), &mut visible);
+        self.visible = visible;
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+        self.ensure_visible();
+    }
+
+    fn layout_scroll_bar(&mut self) {
+        self.scroll_bars.layout(self.scroll_bars.content_area(self.bounds));
+        self.sync_scroll_bar();
+    }
+
+    /// Moves the scroll bar to the current scroll position
+    fn sync_scroll_bar(&mut self) {
+        let (page, max) = (self.page_height(), self.max_scroll_offset());
+        self.scroll_bars.sync((0, self.scroll_offset), (0, max), (0, page));
+    }
+
+    fn page_height(&self) -> usize {
+        self.bounds.height as usize
+    }
+
+    fn max_scroll_offset(&self) -> usize {
+        self.visible.len().saturating_sub(self.page_height())
+    }
+
+    /// Columns left for the nodes
+    fn text_width(&self) -> usize {
+        self.scroll_bars.content_area(self.bounds).width as usize
+    }
+
+    fn ensure_visible(&mut self) {
+        let page = self.page_height().max(1);
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset + page {
+            self.scroll_offset = self.selected + 1 - page;
+        }
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll_offset());
+        self.sync_scroll_bar();
+    }
+
+    fn select(&mut self, index: usize) {
+        if index < self.visible.len() {
+            self.selected = index;
+            self.ensure_visible();
+        }
+    }
+
+    /// Connector lines and expand marker drawn before a node's text
+    fn prefix(node: &VisibleNode, expanded: bool) -> String {
+        let mut prefix = String::new();
+        let depth = node.last.len();
+        for &ancestor_last in &node.last[..depth - 1] {
+            prefix.push_str(if ancestor_last { "  " } else { "│ " });
+        }
+        prefix.push(if node.last[depth - 1] { '└' } else { '├' });
+        prefix.push(match (node.has_children, expanded) {
+            (false, _) => '─',
+            (true, false) => '+',
+            (true, true) => '-',
+        });
+        prefix
+    }
+
+    fn toggle(&mut self, index: usize) {
+        let Some(node) = self.visible.get(index) else { return };
+        let path = node.path.clone();
+        if self.is_expanded(&path) {
+            self.collapse(&path);
+        } else {
+            self.expand(&path);
+        }
+    }
+
+    fn activate(&self, queue: &TEventQueue) {
+        if let Some(path) = self.selected_path() {
+            queue.put_event(TEvent::TreeNodeActivated(self.tree_id, path));
+        }
+    }
+
+    /// Adds `ch` to the search text and selects the first visible node
+    /// starting with it. A char that matches nothing is dropped.
+    fn search_for(&mut self, ch: char) {
+        if self.last_search.is_some_and(|at| at.elapsed() > SEARCH_TIMEOUT) {
+            self.search.clear();
+        }
+        self.last_search = Some(Instant::now());
+        self.search.push(ch);
+        let prefix = self.search.to_lowercase();
+        let found = self
+            .visible
+            .iter()
+            .position(|node| self.source.node_text(&node.path).to_lowercase().starts_with(&prefix));
+        match found {
+            Some(index) => self.select(index),
+            None => {
+                self.search.pop();
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, queue: &TEventQueue) {
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let last = self.visible.len().saturating_sub(1);
+        let page = self.page_height().max(1);
+        let current = self.selected;
+        if !matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+            self.search.clear();
+        }
+        match key.code {
+            KeyCode::Up => self.select(current.saturating_sub(1)),
+            KeyCode::Down => self.select((current + 1).min(last)),
+            KeyCode::PageUp => self.select(current.saturating_sub(page)),
+            KeyCode::PageDown => self.select((current + page).min(last)),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(last),
+            KeyCode::Right => {
+                let Some(node) = self.visible.get(current) else { return };
+                if !node.has_children {
+                    return;
+                }
+                let path = node.path.clone();
+                if self.is_expanded(&path) {
+                    // Already open: move to the first child
+                    if self.visible.get(current + 1).is_some_and(|child| child.path.len() > path.len()) {
+                        self.select(current + 1);
+                    }
+                } else {
+                    self.expand(&path);
+                }
+            }
+            KeyCode::Left => {
+                let Some(node) = self.visible.get(current) else { return };
+                let path = node.path.clone();
+                if self.is_expanded(&path) {
+                    self.collapse(&path);
+                } else if path.len() > 1 {
+                    self.select_path(&path[..path.len() - 1]);
+                }
+            }
+            KeyCode::Char('+') => {
+                if let Some(path) = self.selected_path() {
+                    self.expand(&path);
+                }
+            }
+            KeyCode::Char('-') => {
+                if let Some(path) = self.selected_path() {
+                    self.collapse(&path);
+                }
+            }
+            KeyCode::Enter => self.activate(queue),
+            KeyCode::Char(ch) if plain => self.search_for(ch),
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, queue: &TEventQueue) {
+        if let Some((_, offset)) = self.scroll_bars.handle_mouse(&mouse, (0, self.scroll_offset)) {
+            self.scroll_offset = offset;
+            return;
+        }
+        let pt = TPoint { x: mouse.column, y: mouse.row };
+        if !self.bounds.contains(pt) {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = self.scroll_offset + (mouse.row - self.bounds.y) as usize;
+                let Some(node) = self.visible.get(index) else { return };
+                self.search.clear();
+                // The marker is the last cell of the connector prefix
+                let marker_col = self.bounds.x as usize + node.path.len() * 2 - 1;
+                if node.has_children && mouse.column as usize == marker_col {
+                    self.select(index);
+                    self.toggle(index);
+                    return;
+                }
+                self.select(index);
+                let double = self
+                    .last_click
+                    .is_some_and(|(at, clicked)| clicked == index && at.elapsed() <= DOUBLE_CLICK_TIME);
+                if double {
+                    self.last_click = None;
+                    self.activate(queue);
+                } else {
+                    self.last_click = Some((Instant::now(), index));
+                }
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(WHEEL_STEP);
+                self.sync_scroll_bar();
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_offset = (self.scroll_offset + WHEEL_STEP).min(self.max_scroll_offset());
+                self.sync_scroll_bar();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TView for TTreeView {
+    fn draw(&self, buffer: &mut ScreenBuffer, offset: (u16, u16)) {
+        let x = offset.0 + self.bounds.x;
+        let y = offset.1 + self.bounds.y;
+        let width = self.text_width();
+
+        let end = self.visible.len().min(self.scroll_offset + self.page_height());
+        for (row, index) in (self.scroll_offset..end).enumerate() {
+            let node = &self.visible[index];
+            let prefix = Self::prefix(node, self.is_expanded(&node.path));
+            let prefix_width = prefix.chars().count();
+            let text = format!("{}{}", prefix, self.source.node_text(&node.path));
+            let line: String = format!("{:<width$}", text, width = width).chars().take(width).collect();
+            buffer.write_str(x, y + row as u16, &line);
+            if index == self.selected && self.focused && prefix_width < width {
+                let label: String = line.chars().skip(prefix_width).collect::<String>().trim_end().to_string();
+                buffer.write_styled(x + prefix_width as u16, y + row as u16, &label, SELECTED_FG, SELECTED_BG);
+            }
+        }
+
+        self.scroll_bars.draw(buffer, offset);
+    }
+
+    fn handle_event(&mut self, event: TEvent, queue: &TEventQueue) {
+        let before = self.selected_path();
+        match event {
+            TEvent::Key(key) => self.handle_key(key, queue),
+            TEvent::Mouse(mouse) => self.handle_mouse(mouse, queue),
+            _ => {}
+        }
+        if let Some(path) = self.selected_path().filter(|path| Some(path) != before.as_ref()) {
+            queue.put_event(TEvent::TreeSelectionChanged(self.tree_id, path));
+        }
+    }
+
+    fn get_bounds(&self) -> TRect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: TRect) {
+        self.bounds = bounds;
+        self.layout_scroll_bar();
+        self.ensure_visible();
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn help_context(&self) -> Option<u16> {
+        self.help_context
+    }
+
+    /// Enter activates the selected node
+    fn takes_key(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Enter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Three levels of three nodes, counting how often children are asked for
+    struct Counted(Rc<Cell<usize>>);
+
+    impl TreeDataSource for Counted {
+        fn child_count(&self, path: &[usize]) -> usize {
+            self.0.set(self.0.get() + 1);
+            if path.len() < 3 { 3 } else { 0 }
+        }
+
+        fn node_text(&self, path: &[usize]) -> String {
+            format!("{path:?}")
+        }
+    }
+
+    fn tree() -> (TTreeView, Rc<Cell<usize>>) {
+        let reads = Rc::new(Cell::new(0));
+        (TTreeView::new(TRect::new(0, 0, 30, 10), Box::new(Counted(reads.clone()))), reads)
+    }
+
+    #[test]
+    fn expanding_reads_only_the_new_nodes() {
+        let (mut tree, reads) = tree();
+        // The root and whether each top-level node has children
+        assert_eq!(reads.get(), 4);
+        // Its children, and whether each of them has children
+        tree.expand(&[1]);
+        assert_eq!(reads.get(), 8);
+        assert_eq!(tree.visible.len(), 6);
+
+        tree.collapse(&[1]);
+        tree.expand(&[1]);
+        assert_eq!(reads.get(), 8);
+    }
+
+    #[test]
+    fn refresh_reads_the_data_again() {
+        let (mut tree, reads) = tree();
+        tree.expand(&[0]);
+        tree.select_path(&[0, 2]);
+        let before = reads.get();
+        tree.refresh();
+        assert_eq!(reads.get(), before + 8);
+        assert_eq!(tree.selected_path(), Some(vec![0, 2]));
+    }
+}
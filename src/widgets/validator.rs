@@ -0,0 +1,311 @@
+//! Validators check the text of a [`TInputLine`](crate::widgets::inputline::TInputLine)
+//! while it is typed and once it is complete.
+
+/// Checks input line text. `is_valid_input` runs after every edit and can
+/// reject it; `is_valid` runs on the whole value when focus leaves the
+/// input line or its dialog is accepted.
+pub trait Validator {
+    /// Returns false to reject an edit that produced `text`. May complete
+    /// `text`, e.g. with a picture's literals; `suppress_fill` is set after
+    /// deletions so removed literals are not put back.
+    fn is_valid_input(&self, _text: &mut String, _suppress_fill: bool) -> bool {
+        true
+    }
+
+    fn is_valid(&self, text: &str) -> bool;
+
+    /// Message shown when `is_valid` fails
+    fn error_message(&self) -> String;
+}
+
+/// Accepts only text made of the given chars
+pub struct TFilterValidator {
+    pub valid_chars: String,
+}
+
+impl TFilterValidator {
+    pub fn new(valid_chars: &str) -> Self {
+        Self { valid_chars: valid_chars.to_string() }
+    }
+
+    fn accepts(&self, text: &str) -> bool {
+        text.chars().all(|c| self.valid_chars.contains(c))
+    }
+}
+
+impl Validator for TFilterValidator {
+    fn is_valid_input(&self, text: &mut String, _suppress_fill: bool) -> bool {
+        self.accepts(text)
+    }
+
+    fn is_valid(&self, text: &str) -> bool {
+        self.accepts(text)
+    }
+
+    fn error_message(&self) -> String {
+        "Invalid character in input".to_string()
+    }
+}
+
+/// Accepts integers in `min..=max`
+pub struct TRangeValidator {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl TRangeValidator {
+    pub fn new(min: i64, max: i64) -> Self {
+        Self { min, max }
+    }
+}
+
+impl Validator for TRangeValidator {
+    fn is_valid_input(&self, text: &mut String, _suppress_fill: bool) -> bool {
+        let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+        let sign_ok = !text.starts_with('-') || self.min < 0;
+        sign_ok && digits.chars().all(|c| c.is_ascii_digit())
+    }
+
+    fn is_valid(&self, text: &str) -> bool {
+        text.trim().parse::<i64>().is_ok_and(|value| value >= self.min && value <= self.max)
+    }
+
+    fn error_message(&self) -> String {
+        format!("Value not in the range {} to {}", self.min, self.max)
+    }
+}
+
+/// Accepts decimal numbers in `min..=max`
+pub struct TFloatRangeValidator {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl TFloatRangeValidator {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+}
+
+impl Validator for TFloatRangeValidator {
+    fn is_valid_input(&self, text: &mut String, _suppress_fill: bool) -> bool {
+        let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+        let sign_ok = !text.starts_with('-') || self.min < 0.0;
+        sign_ok
+            && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && digits.matches('.').count() <= 1
+    }
+
+    fn is_valid(&self, text: &str) -> bool {
+        text.trim().parse::<f64>().is_ok_and(|value| value >= self.min && value <= self.max)
+    }
+
+    fn error_message(&self) -> String {
+        format!("Value not in the range {} to {}", self.min, self.max)
+    }
+}
+
+/// Accepts one of a list of strings
+pub struct TStringLookupValidator {
+    pub strings: Vec<String>,
+}
+
+impl TStringLookupValidator {
+    pub fn new(strings: Vec<&str>) -> Self {
+        Self { strings: strings.into_iter().map(String::from).collect() }
+    }
+}
+
+impl Validator for TStringLookupValidator {
+    fn is_valid(&self, text: &str) -> bool {
+        self.strings.iter().any(|s| s == text)
+    }
+
+    fn error_message(&self) -> String {
+        "Input is not in the list of valid strings".to_string()
+    }
+}
+
+/// One position of a picture mask
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PictureChar {
+    Digit,          // '#'
+    Letter,         // '?'
+    UpperLetter,    // '&', letter converted to upper case
+    Any,            // '@'
+    UpperAny,       // '!', any char converted to upper case
+    Literal(char),  // anything else; ';' escapes the next char
+}
+
+impl PictureChar {
+    /// Returns `ch` as it is stored at this position, or `None` if it does not fit
+    fn accept(self, ch: char) -> Option<char> {
+        match self {
+            Self::Digit => ch.is_ascii_digit().then_some(ch),
+            Self::Letter => ch.is_alphabetic().then_some(ch),
+            Self::UpperLetter => ch.is_alphabetic().then(|| ch.to_ascii_uppercase()),
+            Self::Any => Some(ch),
+            Self::UpperAny => Some(ch.to_ascii_uppercase()),
+            Self::Literal(lit) => (lit.eq_ignore_ascii_case(&ch)).then_some(lit),
+        }
+    }
+}
+
+/// Accepts text matching a fixed-length picture mask such as `###-####`:
+/// `#` is a digit, `?` a letter, `&` a letter turned upper case, `@` any
+/// char, `!` any char turned upper case, and other chars are literals
+/// (`;` escapes a mask char). With auto-fill on, literals are typed for
+/// the user as soon as the text reaches them.
+pub struct TPictureValidator {
+    pub picture: String,
+    pub auto_fill: bool,
+    mask: Vec<PictureChar>,
+}
+
+impl TPictureValidator {
+    pub fn new(picture: &str, auto_fill: bool) -> Self {
+        let mut mask = Vec::new();
+        let mut chars = picture.chars();
+        while let Some(ch) = chars.next() {
+            mask.push(match ch {
+                '#' => PictureChar::Digit,
+                '?' => PictureChar::Letter,
+                '&' => PictureChar::UpperLetter,
+                '@' => PictureChar::Any,
+                '!' => PictureChar::UpperAny,
+                ';' => match chars.next() {
+                    Some(escaped) => PictureChar::Literal(escaped),
+                    None => break,
+                },
+                lit => PictureChar::Literal(lit),
+            });
+        }
+        Self { picture: picture.to_string(), auto_fill, mask }
+    }
+
+    /// Fits `text` to the mask, inserting skipped literals when auto-fill
+    /// is on. Returns `None` if it does not match.
+    fn apply(&self, text: &str) -> Option<String> {
+        let mut out = String::new();
+        let mut pos = 0;
+        for ch in text.chars() {
+            loop {
+                let mask_char = *self.mask.get(pos)?;
+                pos += 1;
+                if let Some(accepted) = mask_char.accept(ch) {
+                    out.push(accepted);
+                    break;
+                }
+                match mask_char {
+                    PictureChar::Literal(lit) if self.auto_fill => out.push(lit),
+                    _ => return None,
+                }
+            }
+        }
+        Some(out)
+    }
+}
+
+impl Validator for TPictureValidator {
+    fn is_valid_input(&self, text: &mut String, suppress_fill: bool) -> bool {
+        let Some(mut fitted) = self.apply(text) else {
+            return false;
+        };
+        if self.auto_fill && !suppress_fill {
+            let literals = self.mask[fitted.chars().count()..].iter().map_while(|mask_char| match mask_char {
+                PictureChar::Literal(lit) => Some(*lit),
+                _ => None,
+            });
+            fitted.extend(literals);
+        }
+        *text = fitted;
+        true
+    }
+
+    fn is_valid(&self, text: &str) -> bool {
+        self.apply(text)
+            .is_some_and(|fitted| fitted == text && fitted.chars().count() == self.mask.len())
+    }
+
+    fn error_message(&self) -> String {
+        format!("Input does not match the format {}", self.picture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `is_valid_input` on `text`, returning the completed text if accepted
+    fn input(validator: &dyn Validator, text: &str, suppress_fill: bool) -> Option<String> {
+        let mut text = text.to_string();
+        validator.is_valid_input(&mut text, suppress_fill).then_some(text)
+    }
+
+    #[test]
+    fn picture_fills_literals_as_you_type() {
+        let phone = TPictureValidator::new("###-####", true);
+        assert_eq!(input(&phone, "12", false).as_deref(), Some("12"));
+        assert_eq!(input(&phone, "123", false).as_deref(), Some("123-"));
+        assert_eq!(input(&phone, "1234", false).as_deref(), Some("123-4"));
+        assert_eq!(input(&phone, "123", true).as_deref(), Some("123"));
+        assert_eq!(input(&phone, "12a", false), None);
+        assert_eq!(input(&phone, "123-45678", false), None);
+    }
+
+    #[test]
+    fn picture_without_auto_fill_needs_the_literals() {
+        let phone = TPictureValidator::new("###-####", false);
+        assert_eq!(input(&phone, "123", false).as_deref(), Some("123"));
+        assert_eq!(input(&phone, "1234", false), None);
+        assert_eq!(input(&phone, "123-4", false).as_deref(), Some("123-4"));
+    }
+
+    #[test]
+    fn picture_converts_case_and_escapes_mask_chars() {
+        let code = TPictureValidator::new("&&;#!", true);
+        assert_eq!(input(&code, "ab", false).as_deref(), Some("AB#"));
+        assert_eq!(input(&code, "ab#x", false).as_deref(), Some("AB#X"));
+        assert_eq!(input(&code, "a1", false), None);
+        assert!(code.is_valid("AB#X"));
+        assert!(!code.is_valid("ab#x"));
+    }
+
+    #[test]
+    fn picture_is_valid_only_when_complete() {
+        let date = TPictureValidator::new("##/##/####", true);
+        assert!(date.is_valid("01/02/2024"));
+        assert!(!date.is_valid("01/02/202"));
+        assert!(!date.is_valid("01-02-2024"));
+    }
+
+    #[test]
+    fn range_checks_sign_digits_and_bounds() {
+        let range = TRangeValidator::new(-10, 100);
+        assert!(input(&range, "-5", false).is_some());
+        assert!(input(&range, "+", false).is_some());
+        assert!(input(&range, "1x", false).is_none());
+        assert!(range.is_valid("100"));
+        assert!(range.is_valid(" -10 "));
+        assert!(!range.is_valid("101"));
+        assert!(!range.is_valid("-"));
+
+        let positive = TRangeValidator::new(0, 9);
+        assert!(input(&positive, "-1", false).is_none());
+    }
+
+    #[test]
+    fn float_range_allows_one_decimal_point() {
+        let range = TFloatRangeValidator::new(-1.5, 2.5);
+        assert!(input(&range, "-1.", false).is_some());
+        assert!(input(&range, "1.2.3", false).is_none());
+        assert!(input(&range, "1e3", false).is_none());
+        assert!(range.is_valid("2.5"));
+        assert!(range.is_valid("-1.5"));
+        assert!(!range.is_valid("2.51"));
+        assert!(!range.is_valid("."));
+
+        let positive = TFloatRangeValidator::new(0.0, 1.0);
+        assert!(input(&positive, "-0.5", false).is_none());
+    }
+}